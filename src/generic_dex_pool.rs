@@ -0,0 +1,70 @@
+use scrypto::prelude::*;
+
+// A stand-in for a DEX router that marketplaces can route purchases in other currencies through.
+// It swaps one input token for one output token at a fixed rate set on instantiation - a real router
+// only needs to expose the same swap_exact_output method to be used by a marketplace.
+
+#[blueprint]
+mod generic_dex_pool {
+
+    struct DexPool {
+        /// The tokens the pool pays out
+        output_vault: Vault,
+        /// The tokens the pool takes in
+        input_vault: Vault,
+        /// The amount of output tokens paid out for each input token
+        rate: Decimal,
+    }
+
+    impl DexPool {
+        pub fn start_dex_pool(
+            output_liquidity: Bucket,
+            input_resource: ResourceAddress,
+            rate: Decimal,
+        ) -> Global<DexPool> {
+            assert!(rate > Decimal::zero(), "Rate must be greater than zero");
+
+            Self {
+                output_vault: Vault::with_bucket(output_liquidity),
+                input_vault: Vault::new(input_resource),
+                rate,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /// Swaps just enough of the input for an exact amount of the output, returning the output and the unspent input.
+        pub fn swap_exact_output(
+            &mut self,
+            mut input: Bucket,
+            output_resource: ResourceAddress,
+            output_amount: Decimal,
+        ) -> (Bucket, Bucket) {
+            assert!(
+                input.resource_address() == self.input_vault.resource_address(),
+                "[swap_exact_output] Incorrect input resource"
+            );
+
+            assert!(
+                output_resource == self.output_vault.resource_address(),
+                "[swap_exact_output] Incorrect output resource"
+            );
+
+            let input_amount = output_amount
+                .checked_div(self.rate)
+                .unwrap()
+                .checked_round(18, RoundingMode::ToPositiveInfinity)
+                .unwrap();
+
+            assert!(
+                input.amount() >= input_amount,
+                "[swap_exact_output] Insufficient input for the requested output"
+            );
+
+            self.input_vault.put(input.take(input_amount));
+
+            (self.output_vault.take(output_amount), input)
+        }
+    }
+}
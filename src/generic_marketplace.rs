@@ -1,3 +1,5 @@
+use crate::marketplace_events::MarketplaceFeesWithdrawn;
+use crate::open_trader_account::Listing;
 use scrypto::prelude::*;
 
 #[derive(ScryptoSbor, NonFungibleData)]
@@ -9,15 +11,56 @@ struct MarketPlacePermission {
 struct AdminKey {}
 
 #[blueprint]
+#[events(MarketplaceFeesWithdrawn)]
 mod generic_marketplace {
 
+    enable_method_auth! {
+    roles {
+        admin => updatable_by: [];
+    },
+    methods {
+        purchase_royal_listing => PUBLIC;
+        purchase_bundle => PUBLIC;
+        purchase_listing => PUBLIC;
+        purchase_listing_as_buyer => PUBLIC;
+        sweep_listings => PUBLIC;
+        purchase_listing_with_swap => PUBLIC;
+        purchase_royal_listing_with_swap => PUBLIC;
+        set_dex_router => restrict_to: [admin];
+        fee_rate_for => PUBLIC;
+        set_collection_fee => restrict_to: [admin];
+        remove_collection_fee => restrict_to: [admin];
+        set_volume_tiers => restrict_to: [admin];
+        add_promotion => restrict_to: [admin];
+        clear_promotions => restrict_to: [admin];
+        purchase_preview_mint => PUBLIC;
+        get_marketplace_key_address => PUBLIC;
+        fee_balance => PUBLIC;
+        withdraw_fees => restrict_to: [admin];
+        withdraw_all_fees => restrict_to: [admin];
+    }
+    }
+
     struct GenericMarketplace {
         marketplace_listing_key_vault: Vault,
         marketplace_key_manager: ResourceManager,
         marketplace_admin: ResourceManager,
         marketplace_fee: Decimal,
         fee_vaults: KeyValueStore<ResourceAddress, Vault>,
+        /// The currencies that fee vaults have been created for - used to sweep all fees in one call
+        fee_currencies: Vec<ResourceAddress>,
         mint_fee: Decimal,
+        /// The DEX router used to convert payments into a listing's currency. It must expose
+        /// swap_exact_output(input: Bucket, output_resource: ResourceAddress, output_amount: Decimal) -> (Bucket, Bucket)
+        dex_router: Option<ComponentAddress>,
+        /// Fee rates for specific collections that override the marketplace fee
+        collection_fees: KeyValueStore<ResourceAddress, Decimal>,
+        /// Discounted fee rates for buyers who have made at least a number of purchases - (minimum purchases, fee rate)
+        volume_tiers: Vec<(u64, Decimal)>,
+        /// The number of purchases each buyer account has made through the marketplace
+        buyer_purchases: KeyValueStore<ComponentAddress, u64>,
+        /// Promotional windows (start, end) during which no marketplace fee is charged
+        promotions: Vec<(Instant, Instant)>,
     }
 
     impl GenericMarketplace {
@@ -62,7 +105,13 @@ mod generic_marketplace {
                 marketplace_admin: admin_key.resource_manager(),
                 marketplace_fee,
                 fee_vaults: KeyValueStore::new(),
+                fee_currencies: vec![],
                 mint_fee,
+                dex_router: None,
+                collection_fees: KeyValueStore::new(),
+                volume_tiers: vec![],
+                buyer_purchases: KeyValueStore::new(),
+                promotions: vec![],
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -80,12 +129,16 @@ mod generic_marketplace {
                     "icon_url" => Url::of("https://trove.tools/trove%20square.png"), locked;
                 }
             ))
+            .roles(roles!(
+                admin => rule!(require(admin_key.resource_address()));
+            ))
             .with_address(marketplace_address_reservation)
             .globalize();
 
             (component_address, admin_key.into())
         }
 
+        /// The royal NFT is deposited to the account recipient, so its fee tier is used and the purchase counts towards it.
         pub fn purchase_royal_listing(
             &mut self,
             nfgid: NonFungibleGlobalId,
@@ -93,6 +146,9 @@ mod generic_marketplace {
             open_sale_address: Global<AnyComponent>,
             account_recipient: Global<Account>,
         ) -> Vec<Bucket> {
+            let fee_rate = self
+                .scheduled_fee_rate(nfgid.resource_address(), Some(account_recipient.address()));
+
             let nflid = NonFungibleLocalId::integer(1u64.into());
             let proof_creation: Proof = self
                 .marketplace_listing_key_vault
@@ -100,36 +156,57 @@ mod generic_marketplace {
                 .create_proof_of_non_fungibles(&indexset![nflid])
                 .into();
 
-            let mut fee_and_receipt: (Vec<Bucket>, Vec<Bucket>) =
-                open_sale_address.call_raw::<(Vec<Bucket>, Vec<Bucket>)>(
-                    "purchase_royal_listing",
-                    scrypto_args!(nfgid, payment, proof_creation, account_recipient),
+            let (fees, mut receipts, change) =
+                open_sale_address.call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                    "purchase_royal_listing_with_fee_rate",
+                    scrypto_args!(nfgid, payment, proof_creation, account_recipient, fee_rate),
                 );
 
-            let fee_returned = fee_and_receipt.0.pop().unwrap();
+            for fee_returned in fees {
+                self.store_fee(fee_returned);
+            }
 
-            let fee_resource = fee_returned.resource_address();
+            receipts.extend(change);
 
-            let fee_vault_exists = self.fee_vaults.get(&fee_resource).is_some();
+            receipts
+        }
 
-            if fee_vault_exists {
-                self.fee_vaults
-                    .get_mut(&fee_resource)
-                    .unwrap()
-                    .put(fee_returned);
-            } else {
-                let fee_vault = Vault::with_bucket(fee_returned);
-                self.fee_vaults.insert(fee_resource, fee_vault);
-            }
+        /// Standard NFTs are returned to the caller, so no buyer is known - collection fees and promotions apply, but volume
+        /// tiers need purchase_listing_as_buyer.
+        pub fn purchase_listing(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            trader_account_address: Global<AnyComponent>,
+        ) -> Vec<Bucket> {
+            let fee_rate = self.scheduled_fee_rate(nfgid.resource_address(), None);
 
-            fee_and_receipt.1
+            self.purchase_listing_at_fee_rate(nfgid, payment, trader_account_address, fee_rate)
         }
 
-        pub fn purchase_listing(
+        /// Purchases a listing at the buyer's volume tier, counting the purchase towards it. The caller must prove they own
+        /// the buyer account.
+        pub fn purchase_listing_as_buyer(
             &mut self,
             nfgid: NonFungibleGlobalId,
             payment: FungibleBucket,
             trader_account_address: Global<AnyComponent>,
+            account: Global<Account>,
+        ) -> Vec<Bucket> {
+            Runtime::assert_access_rule(account.get_owner_role().rule);
+
+            let fee_rate =
+                self.scheduled_fee_rate(nfgid.resource_address(), Some(account.address()));
+
+            self.purchase_listing_at_fee_rate(nfgid, payment, trader_account_address, fee_rate)
+        }
+
+        fn purchase_listing_at_fee_rate(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            trader_account_address: Global<AnyComponent>,
+            fee_rate: Decimal,
         ) -> Vec<Bucket> {
             let nflid = NonFungibleLocalId::integer(1u64.into());
             let proof_creation: Proof = self
@@ -138,29 +215,306 @@ mod generic_marketplace {
                 .create_proof_of_non_fungibles(&indexset![nflid])
                 .into();
 
-            let mut fee_and_nft: (Vec<Bucket>, Vec<Bucket>) =
-                trader_account_address.call_raw::<(Vec<Bucket>, Vec<Bucket>)>(
-                    "purchase_listing",
-                    scrypto_args!(nfgid, payment, proof_creation),
+            let (mut nft, fees, change) =
+                trader_account_address.call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                    "purchase_listing_with_fee_rate",
+                    scrypto_args!(nfgid, payment, proof_creation, fee_rate),
                 );
 
-            let fee_returned = fee_and_nft.1.pop().unwrap();
+            for fee_returned in fees {
+                self.store_fee(fee_returned);
+            }
 
-            let fee_resource = fee_returned.resource_address();
+            nft.extend(change);
 
-            let fee_vault_exists = self.fee_vaults.get(&fee_resource).is_some();
+            nft
+        }
 
-            if fee_vault_exists {
-                self.fee_vaults
-                    .get_mut(&fee_resource)
-                    .unwrap()
-                    .put(fee_returned);
+        /// Purchases a bundle listed in a trader account. Royalty NFTs are deposited straight to the account recipient,
+        /// while standard NFTs are returned along with any change from a payment above the bundle price.
+        pub fn purchase_bundle(
+            &mut self,
+            bundle_id: u64,
+            payment: FungibleBucket,
+            trader_account_address: Global<AnyComponent>,
+            account_recipient: Global<Account>,
+        ) -> Vec<Bucket> {
+            let nflid = NonFungibleLocalId::integer(1);
+            let proof_creation: Proof = self
+                .marketplace_listing_key_vault
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![nflid])
+                .into();
+
+            let (fees, mut nfts, change) =
+                trader_account_address.call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                    "purchase_bundle",
+                    scrypto_args!(bundle_id, payment, proof_creation, account_recipient),
+                );
+
+            for fee_returned in fees {
+                self.store_fee(fee_returned);
+            }
+
+            nfts.extend(change);
+
+            nfts
+        }
+
+        /// Sweeps the floor - buys each listing in order with a single payment, as long as it is still listed, hasn't expired,
+        /// permits this marketplace, accepts the payment currency, accepts this marketplace's fee and is priced at or below its
+        /// max price. Listings that don't are skipped rather than failing the whole sweep. The purchased NFTs/receipts are
+        /// returned along with the change, and the marketplace fees of every purchase are stored together.
+        ///
+        /// Only what a listing shows can be checked up front. A purchase still aborts the whole sweep if the seller's
+        /// account-wide maximum marketplace fee is below this marketplace's fee, a royalty NFT's creator limits the buyers,
+        /// currencies or dApps it can be traded with, or a royalty NFT was listed in this same transaction - so a sweep
+        /// should only include listings a single purchase would succeed on.
+        pub fn sweep_listings(
+            &mut self,
+            listings: Vec<(Global<AnyComponent>, NonFungibleGlobalId, Decimal)>,
+            mut payment: FungibleBucket,
+            account_recipient: Global<Account>,
+        ) -> Vec<Bucket> {
+            let currency = payment.resource_address();
+            let marketplace_key = self.marketplace_listing_key_vault.resource_address();
+            // Standard NFTs are returned to the caller rather than the recipient account, so the caller must prove
+            // they own the account their fee tier is taken from.
+            let mut recipient_owner_checked = false;
+
+            let mut purchased: Vec<Bucket> = vec![];
+            let mut fees = Bucket::new(currency);
+
+            for (trader_account_address, nfgid, max_price) in listings {
+                let listing: Option<Listing> = trader_account_address
+                    .call_raw::<Option<Listing>>("get_listing", scrypto_args!(nfgid.clone()));
+
+                let listing = match listing {
+                    Some(listing) => listing,
+                    None => continue,
+                };
+
+                if listing.is_expired()
+                    || !listing
+                        .secondary_seller_permissions
+                        .contains(&marketplace_key)
+                    || (listing.currency != currency
+                        && !listing.alternative_prices.contains_key(&currency))
+                {
+                    continue;
+                }
+
+                let price = listing.current_price_in(currency);
+
+                if price > max_price || price > payment.amount() {
+                    continue;
+                }
+
+                let fee_rate =
+                    self.fee_rate_for(nfgid.resource_address(), Some(account_recipient.address()));
+
+                if listing
+                    .max_marketplace_fee
+                    .is_some_and(|max_marketplace_fee| fee_rate > max_marketplace_fee)
+                {
+                    continue;
+                }
+
+                let nflid = NonFungibleLocalId::integer(1);
+                let proof_creation: Proof = self
+                    .marketplace_listing_key_vault
+                    .as_non_fungible()
+                    .create_proof_of_non_fungibles(&indexset![nflid])
+                    .into();
+
+                let royalty_component: Option<GlobalAddress> =
+                    ResourceManager::from_address(nfgid.resource_address())
+                        .get_metadata("royalty_component")
+                        .unwrap_or(None);
+
+                if royalty_component.is_none() && !recipient_owner_checked {
+                    Runtime::assert_access_rule(account_recipient.get_owner_role().rule);
+                    recipient_owner_checked = true;
+                }
+
+                self.count_purchase(account_recipient.address());
+
+                if royalty_component.is_some() {
+                    let (fee_returned, receipts, change) =
+                        trader_account_address
+                            .call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                                "purchase_royal_listing_with_fee_rate",
+                                scrypto_args!(
+                                    nfgid,
+                                    payment.take(price),
+                                    proof_creation,
+                                    account_recipient,
+                                    fee_rate
+                                ),
+                            );
+
+                    fee_returned.into_iter().for_each(|fee| fees.put(fee));
+                    purchased.extend(receipts);
+                    change
+                        .into_iter()
+                        .for_each(|change| payment.put(change.as_fungible()));
+                } else {
+                    let (nft, fee_returned, change) =
+                        trader_account_address
+                            .call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                                "purchase_listing_with_fee_rate",
+                                scrypto_args!(nfgid, payment.take(price), proof_creation, fee_rate),
+                            );
+
+                    fee_returned.into_iter().for_each(|fee| fees.put(fee));
+                    purchased.extend(nft);
+                    change
+                        .into_iter()
+                        .for_each(|change| payment.put(change.as_fungible()));
+                }
+            }
+
+            if fees.is_empty() {
+                fees.drop_empty();
             } else {
-                let fee_vault = Vault::with_bucket(fee_returned);
-                self.fee_vaults.insert(fee_resource, fee_vault);
+                self.store_fee(fees);
             }
 
-            fee_and_nft.0
+            purchased.push(payment.into());
+
+            purchased
+        }
+
+        /// Purchases a listing with a payment in any currency the DEX router can convert into the listing's currency.
+        /// At most max_input of the payment is swapped, protecting the buyer from slippage - anything not swapped is returned.
+        pub fn purchase_listing_with_swap(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            max_input: Decimal,
+            trader_account_address: Global<AnyComponent>,
+        ) -> Vec<Bucket> {
+            let (converted_payment, mut change) =
+                self.convert_payment(&nfgid, payment, max_input, trader_account_address);
+
+            let mut nft_and_change =
+                self.purchase_listing(nfgid, converted_payment, trader_account_address);
+
+            nft_and_change.append(&mut change);
+
+            nft_and_change
+        }
+
+        /// The royalty enforced version of purchase_listing_with_swap.
+        pub fn purchase_royal_listing_with_swap(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            max_input: Decimal,
+            open_sale_address: Global<AnyComponent>,
+            account_recipient: Global<Account>,
+        ) -> Vec<Bucket> {
+            let (converted_payment, mut change) =
+                self.convert_payment(&nfgid, payment, max_input, open_sale_address);
+
+            let mut receipt_and_change = self.purchase_royal_listing(
+                nfgid,
+                converted_payment,
+                open_sale_address,
+                account_recipient,
+            );
+
+            receipt_and_change.append(&mut change);
+
+            receipt_and_change
+        }
+
+        pub fn set_dex_router(&mut self, dex_router: Option<ComponentAddress>) {
+            self.dex_router = dex_router;
+        }
+
+        /// The fee rate a buyer pays on a collection right now. No fee is charged during a promotion - otherwise the
+        /// collection's fee (or the marketplace fee) is used, discounted to the buyer's volume tier rate if that's lower.
+        /// Without a buyer no volume tier applies. The rate is never above the marketplace fee published on the marketplace key.
+        pub fn fee_rate_for(
+            &self,
+            collection: ResourceAddress,
+            buyer: Option<ComponentAddress>,
+        ) -> Decimal {
+            let in_promotion = self.promotions.iter().any(|(start, end)| {
+                Clock::current_time_is_at_or_after(*start, TimePrecision::Second)
+                    && Clock::current_time_is_strictly_before(*end, TimePrecision::Second)
+            });
+
+            if in_promotion {
+                return Decimal::zero();
+            }
+
+            let fee_rate = self
+                .collection_fees
+                .get(&collection)
+                .map(|collection_fee| *collection_fee)
+                .unwrap_or(self.marketplace_fee);
+
+            let buyer = match buyer {
+                Some(buyer) => buyer,
+                None => return fee_rate,
+            };
+
+            let buyer_purchases = self
+                .buyer_purchases
+                .get(&buyer)
+                .map(|purchases| *purchases)
+                .unwrap_or(0);
+
+            self.volume_tiers
+                .iter()
+                .filter(|(minimum_purchases, _)| buyer_purchases >= *minimum_purchases)
+                .fold(fee_rate, |fee_rate, (_, tier_rate)| {
+                    fee_rate.min(*tier_rate)
+                })
+        }
+
+        /// Sets a collection's fee rate. Sellers agree to the fee published on the marketplace key, so a collection fee
+        /// can only discount it.
+        pub fn set_collection_fee(&mut self, collection: ResourceAddress, fee_rate: Decimal) {
+            assert!(
+                fee_rate >= Decimal::zero() && fee_rate <= self.marketplace_fee,
+                "[set_collection_fee] Fee rate must be between 0 and the marketplace fee"
+            );
+
+            self.collection_fees.insert(collection, fee_rate);
+        }
+
+        pub fn remove_collection_fee(&mut self, collection: ResourceAddress) {
+            self.collection_fees.remove(&collection);
+        }
+
+        /// Replaces the volume tiers - each tier is the minimum number of purchases a buyer must have made and its fee rate.
+        /// Like collection fees, a tier can only discount the marketplace fee.
+        pub fn set_volume_tiers(&mut self, volume_tiers: Vec<(u64, Decimal)>) {
+            for (_, fee_rate) in volume_tiers.iter() {
+                assert!(
+                    *fee_rate >= Decimal::zero() && *fee_rate <= self.marketplace_fee,
+                    "[set_volume_tiers] Fee rate must be between 0 and the marketplace fee"
+                );
+            }
+
+            self.volume_tiers = volume_tiers;
+        }
+
+        /// Adds a window of time during which no marketplace fee is charged.
+        pub fn add_promotion(&mut self, start: Instant, end: Instant) {
+            assert!(
+                start.compare(end, TimeComparisonOperator::Lt),
+                "[add_promotion] Promotion must start before it ends"
+            );
+
+            self.promotions.push((start, end));
+        }
+
+        pub fn clear_promotions(&mut self) {
+            self.promotions = vec![];
         }
 
         pub fn purchase_preview_mint(
@@ -174,15 +528,7 @@ mod generic_marketplace {
             let fee =
                 payment.take_advanced(fee_amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
 
-            let fee_resource = fee.resource_address();
-            let fee_vault_exists = self.fee_vaults.get(&fee_resource).is_some();
-
-            if fee_vault_exists {
-                self.fee_vaults.get_mut(&fee_resource).unwrap().put(fee);
-            } else {
-                let fee_vault = Vault::with_bucket(fee);
-                self.fee_vaults.insert(fee_resource, fee_vault);
-            }
+            self.store_fee(fee);
 
             let nflid = NonFungibleLocalId::integer(1u64.into());
             let proof_creation: Proof = self
@@ -202,5 +548,134 @@ mod generic_marketplace {
         pub fn get_marketplace_key_address(&self) -> ResourceAddress {
             self.marketplace_listing_key_vault.resource_address()
         }
+
+        pub fn fee_balance(&self, currency: ResourceAddress) -> Decimal {
+            match self.fee_vaults.get(&currency) {
+                Some(vault) => vault.amount(),
+                None => Decimal::zero(),
+            }
+        }
+
+        /// Withdraws an amount of collected fees in a given currency. Only the AdminKey holder can withdraw fees.
+        pub fn withdraw_fees(&mut self, currency: ResourceAddress, amount: Decimal) -> Bucket {
+            let fees = {
+                let mut vault = self
+                    .fee_vaults
+                    .get_mut(&currency)
+                    .expect("[withdraw_fees] No fees have been collected in this currency");
+
+                assert!(
+                    amount <= vault.amount(),
+                    "[withdraw_fees] Insufficient fees to withdraw"
+                );
+
+                vault.take(amount)
+            };
+
+            Runtime::emit_event(MarketplaceFeesWithdrawn {
+                marketplace: Runtime::global_address(),
+                currency,
+                amount: fees.amount(),
+            });
+
+            fees
+        }
+
+        /// Sweeps the fees collected in every currency, emitting a withdrawal event for each one.
+        pub fn withdraw_all_fees(&mut self) -> Vec<Bucket> {
+            let mut fees: Vec<Bucket> = vec![];
+
+            for currency in self.fee_currencies.iter() {
+                let mut vault = self.fee_vaults.get_mut(currency).unwrap();
+
+                if !vault.is_empty() {
+                    let fee = vault.take_all();
+
+                    Runtime::emit_event(MarketplaceFeesWithdrawn {
+                        marketplace: Runtime::global_address(),
+                        currency: *currency,
+                        amount: fee.amount(),
+                    });
+
+                    fees.push(fee);
+                }
+            }
+
+            fees
+        }
+
+        /// Swaps up to max_input of a payment through the DEX router into exactly the current price of a listing,
+        /// so the trader account's payment assertions are met. Returns the converted payment and the unspent payment.
+        fn convert_payment(
+            &self,
+            nfgid: &NonFungibleGlobalId,
+            mut payment: FungibleBucket,
+            max_input: Decimal,
+            trader_account_address: Global<AnyComponent>,
+        ) -> (FungibleBucket, Vec<Bucket>) {
+            let dex_router: Global<AnyComponent> = Global::from(
+                self.dex_router
+                    .expect("[purchase_with_swap] No DEX router has been set"),
+            );
+
+            let listing: Listing = trader_account_address
+                .call_raw::<Option<Listing>>("get_listing", scrypto_args!(nfgid.clone()))
+                .expect("[purchase_with_swap] Listing not found");
+
+            let price = listing.current_price();
+
+            let (converted_payment, unspent_input): (Bucket, Bucket) = dex_router.call_raw(
+                "swap_exact_output",
+                scrypto_args!(
+                    payment.take(max_input.min(payment.amount())),
+                    listing.currency,
+                    price
+                ),
+            );
+
+            (
+                converted_payment.as_fungible(),
+                vec![unspent_input, payment.into()],
+            )
+        }
+
+        /// Finds the fee rate to pass to the trader account for a purchase, counting the purchase towards the buyer's volume tier.
+        fn scheduled_fee_rate(
+            &mut self,
+            collection: ResourceAddress,
+            buyer: Option<ComponentAddress>,
+        ) -> Decimal {
+            let fee_rate = self.fee_rate_for(collection, buyer);
+
+            if let Some(buyer) = buyer {
+                self.count_purchase(buyer);
+            }
+
+            fee_rate
+        }
+
+        fn count_purchase(&mut self, buyer: ComponentAddress) {
+            let buyer_purchases = self
+                .buyer_purchases
+                .get(&buyer)
+                .map(|purchases| *purchases)
+                .unwrap_or(0);
+
+            self.buyer_purchases.insert(buyer, buyer_purchases + 1);
+        }
+
+        fn store_fee(&mut self, fee: Bucket) {
+            let fee_resource = fee.resource_address();
+
+            let fee_vault_exists = self.fee_vaults.get(&fee_resource).is_some();
+
+            if fee_vault_exists {
+                self.fee_vaults.get_mut(&fee_resource).unwrap().put(fee);
+            } else {
+                let fee_vault = Vault::with_bucket(fee);
+                self.fee_vaults.insert(fee_resource, fee_vault);
+                self.fee_currencies.push(fee_resource);
+            }
+        }
     }
 }
@@ -1,5 +1,7 @@
 pub mod generic_dapp;
+pub mod generic_dex_pool;
 pub mod generic_marketplace;
+pub mod marketplace_events;
 pub mod mint_factory;
 pub mod niche_marketplace;
 pub mod open_trade_event;
@@ -0,0 +1,9 @@
+use scrypto::prelude::*;
+
+/// Emitted by both `GenericMarketplace` and `Marketplace` whenever the `AdminKey` holder withdraws collected fees.
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct MarketplaceFeesWithdrawn {
+    pub marketplace: ComponentAddress,
+    pub currency: ResourceAddress,
+    pub amount: Decimal,
+}
@@ -1,3 +1,4 @@
+use crate::marketplace_events::MarketplaceFeesWithdrawn;
 use scrypto::prelude::*;
 
 #[derive(ScryptoSbor, NonFungibleData)]
@@ -7,14 +8,29 @@ struct MarketPlacePermission {}
 struct AdminKey {}
 
 #[blueprint]
+#[events(MarketplaceFeesWithdrawn)]
 mod niche_marketplace {
 
+    enable_method_auth! {
+    roles {
+        admin => updatable_by: [];
+    },
+    methods {
+        purchase_royal_listing => PUBLIC;
+        purchase_bundle => PUBLIC;
+        fee_balance => PUBLIC;
+        withdraw_fees => restrict_to: [admin];
+        withdraw_all_fees => restrict_to: [admin];
+    }
+    }
+
     struct Marketplace {
         marketplace_listing_key_vault: Vault,
         marketplace_key_manager: ResourceManager,
         marketplace_admin: ResourceManager,
         marketplace_fee: Decimal,
         fee_vaults: KeyValueStore<ResourceAddress, Vault>,
+        fee_currencies: Vec<ResourceAddress>,
     }
 
     impl Marketplace {
@@ -61,9 +77,13 @@ mod niche_marketplace {
                 marketplace_admin: admin_key.resource_manager(),
                 marketplace_fee,
                 fee_vaults: KeyValueStore::new(),
+                fee_currencies: vec![],
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                admin => rule!(require(admin_key.resource_address()));
+            ))
             .with_address(marketplace_address_reservation)
             .globalize();
 
@@ -76,24 +96,113 @@ mod niche_marketplace {
             payment: FungibleBucket,
             open_sale_address: Global<AnyComponent>,
             account_recipient: Global<Account>,
-        ) -> Bucket {
+        ) -> Vec<Bucket> {
             let nflid = NonFungibleLocalId::integer(1u64.into());
             let proof_creation = self
                 .marketplace_listing_key_vault
                 .as_non_fungible()
                 .create_proof_of_non_fungibles(&indexset![nflid]);
 
-            let (nft, fee): (Bucket, Bucket) =
-                open_sale_address.call_raw::<(Bucket, Bucket)>(
+            let (fees, mut receipts, change) = open_sale_address
+                .call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
                     "purchase_royal_listing",
-                    scrypto_args!(
-                        nfgid,
-                        payment,
-                        proof_creation,
-                        account_recipient
-                    ),
+                    scrypto_args!(nfgid, payment, proof_creation, account_recipient),
+                );
+
+            for fee in fees {
+                self.store_fee(fee);
+            }
+
+            receipts.extend(change);
+
+            receipts
+        }
+
+        /// Purchases a bundle listed in a trader account. Royalty NFTs are deposited straight to the account recipient,
+        /// while standard NFTs are returned along with any change from a payment above the bundle price.
+        pub fn purchase_bundle(
+            &mut self,
+            bundle_id: u64,
+            payment: FungibleBucket,
+            trader_account_address: Global<AnyComponent>,
+            account_recipient: Global<Account>,
+        ) -> Vec<Bucket> {
+            let nflid = NonFungibleLocalId::integer(1);
+            let proof_creation: Proof = self
+                .marketplace_listing_key_vault
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![nflid])
+                .into();
+
+            let (fees, mut nfts, change) =
+                trader_account_address.call_raw::<(Vec<Bucket>, Vec<Bucket>, Option<Bucket>)>(
+                    "purchase_bundle",
+                    scrypto_args!(bundle_id, payment, proof_creation, account_recipient),
+                );
+
+            for fee in fees {
+                self.store_fee(fee);
+            }
+
+            nfts.extend(change);
+
+            nfts
+        }
+
+        pub fn fee_balance(&self, currency: ResourceAddress) -> Decimal {
+            match self.fee_vaults.get(&currency) {
+                Some(vault) => vault.amount(),
+                None => Decimal::zero(),
+            }
+        }
+
+        pub fn withdraw_fees(&mut self, currency: ResourceAddress, amount: Decimal) -> Bucket {
+            let fees = {
+                let mut vault = self
+                    .fee_vaults
+                    .get_mut(&currency)
+                    .expect("[withdraw_fees] No fees have been collected in this currency");
+
+                assert!(
+                    amount <= vault.amount(),
+                    "[withdraw_fees] Insufficient fees to withdraw"
                 );
 
+                vault.take(amount)
+            };
+
+            Runtime::emit_event(MarketplaceFeesWithdrawn {
+                marketplace: Runtime::global_address(),
+                currency,
+                amount: fees.amount(),
+            });
+
+            fees
+        }
+
+        pub fn withdraw_all_fees(&mut self) -> Vec<Bucket> {
+            let mut fees: Vec<Bucket> = vec![];
+
+            for currency in self.fee_currencies.iter() {
+                let mut vault = self.fee_vaults.get_mut(currency).unwrap();
+
+                if !vault.is_empty() {
+                    let fee = vault.take_all();
+
+                    Runtime::emit_event(MarketplaceFeesWithdrawn {
+                        marketplace: Runtime::global_address(),
+                        currency: *currency,
+                        amount: fee.amount(),
+                    });
+
+                    fees.push(fee);
+                }
+            }
+
+            fees
+        }
+
+        fn store_fee(&mut self, fee: Bucket) {
             let fee_resource = fee.resource_address();
 
             let fee_vault_exists = self.fee_vaults.get(&fee_resource).is_some();
@@ -103,9 +212,8 @@ mod niche_marketplace {
             } else {
                 let fee_vault = Vault::with_bucket(fee);
                 self.fee_vaults.insert(fee_resource, fee_vault);
+                self.fee_currencies.push(fee_resource);
             }
-
-            nft
         }
     }
 }
@@ -1,6 +1,11 @@
 use scrypto::prelude::*;
 
+use crate::open_trader_account::Auction;
+use crate::open_trader_account::Bundle;
 use crate::open_trader_account::Listing;
+use crate::open_trader_account::ListingBatchAction;
+use crate::open_trader_account::Offer;
+use crate::open_trader_account::Swap;
 /// This component acts as the central hub for all trade emitted events, such as listing creation, listing updates, listing cancellations, and listing purchases.
 
 #[derive(ScryptoSbor, ScryptoEvent)]
@@ -31,8 +36,143 @@ struct ListingPurchased {
     nft_id: NonFungibleGlobalId,
 }
 
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ListingExpired {
+    listing: Listing,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+/// A single event for a batch of listings, so that listing, repricing or cancelling many NFTs at once only
+/// calls the event emitter component once.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ListingsBatched {
+    action: ListingBatchAction,
+    listings: Vec<Listing>,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OfferCreated {
+    offer: Offer,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OfferUpdated {
+    offer: Offer,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OfferCanceled {
+    offer: Offer,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OfferAccepted {
+    offer: Offer,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+    price: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AuctionCreated {
+    auction: Auction,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AuctionBidPlaced {
+    auction: Auction,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AuctionExtended {
+    auction: Auction,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AuctionSettled {
+    auction: Auction,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AuctionCanceled {
+    auction: Auction,
+    open_trader_account: ComponentAddress,
+    nft_id: NonFungibleGlobalId,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct BundleCreated {
+    bundle: Bundle,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct BundleCanceled {
+    bundle: Bundle,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct BundlePurchased {
+    bundle: Bundle,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SwapCreated {
+    swap: Swap,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SwapCanceled {
+    swap: Swap,
+    open_trader_account: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SwapAccepted {
+    swap: Swap,
+    open_trader_account: ComponentAddress,
+}
+
 #[blueprint]
-#[events(ListingCreated, ListingUpdated, ListingCanceled, ListingPurchased)]
+#[events(
+    ListingCreated,
+    ListingUpdated,
+    ListingCanceled,
+    ListingPurchased,
+    ListingExpired,
+    ListingsBatched,
+    OfferCreated,
+    OfferUpdated,
+    OfferCanceled,
+    OfferAccepted,
+    AuctionCreated,
+    AuctionBidPlaced,
+    AuctionExtended,
+    AuctionSettled,
+    AuctionCanceled,
+    BundleCreated,
+    BundleCanceled,
+    BundlePurchased,
+    SwapCreated,
+    SwapCanceled,
+    SwapAccepted
+)]
 mod event {
 
     struct Event {
@@ -107,5 +247,166 @@ mod event {
                 nft_id,
             });
         }
+
+        pub fn expire_listing_event(
+            &self,
+            listing: Listing,
+            nft_id: NonFungibleGlobalId,
+            emitter_badge: Proof,
+        ) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(ListingExpired {
+                listing: listing.clone(),
+                open_trader_account: listing.open_trader_account,
+                nft_id,
+            });
+        }
+
+        pub fn batch_listing_event(
+            &self,
+            action: ListingBatchAction,
+            listings: Vec<Listing>,
+            emitter_badge: Proof,
+        ) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(ListingsBatched {
+                action,
+                open_trader_account: listings[0].open_trader_account,
+                listings,
+            });
+        }
+
+        pub fn offer_event(&self, offer: Offer, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(OfferCreated {
+                offer: offer.clone(),
+                open_trader_account: offer.open_trader_account,
+            });
+        }
+
+        pub fn update_offer_event(&self, offer: Offer, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(OfferUpdated {
+                offer: offer.clone(),
+                open_trader_account: offer.open_trader_account,
+            });
+        }
+
+        pub fn cancel_offer_event(&self, offer: Offer, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(OfferCanceled {
+                offer: offer.clone(),
+                open_trader_account: offer.open_trader_account,
+            });
+        }
+
+        pub fn accept_offer_event(
+            &self,
+            offer: Offer,
+            nft_id: NonFungibleGlobalId,
+            price: Decimal,
+            emitter_badge: Proof,
+        ) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(OfferAccepted {
+                offer: offer.clone(),
+                open_trader_account: offer.open_trader_account,
+                nft_id,
+                price,
+            });
+        }
+
+        pub fn auction_event(&self, auction: Auction, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(AuctionCreated {
+                auction: auction.clone(),
+                open_trader_account: auction.open_trader_account,
+                nft_id: auction.nfgid,
+            });
+        }
+
+        pub fn auction_bid_event(&self, auction: Auction, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(AuctionBidPlaced {
+                auction: auction.clone(),
+                open_trader_account: auction.open_trader_account,
+                nft_id: auction.nfgid,
+            });
+        }
+
+        pub fn auction_extended_event(&self, auction: Auction, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(AuctionExtended {
+                auction: auction.clone(),
+                open_trader_account: auction.open_trader_account,
+                nft_id: auction.nfgid,
+            });
+        }
+
+        pub fn auction_settled_event(&self, auction: Auction, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(AuctionSettled {
+                auction: auction.clone(),
+                open_trader_account: auction.open_trader_account,
+                nft_id: auction.nfgid,
+            });
+        }
+
+        pub fn auction_canceled_event(&self, auction: Auction, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(AuctionCanceled {
+                auction: auction.clone(),
+                open_trader_account: auction.open_trader_account,
+                nft_id: auction.nfgid,
+            });
+        }
+
+        pub fn bundle_event(&self, bundle: Bundle, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(BundleCreated {
+                bundle: bundle.clone(),
+                open_trader_account: bundle.open_trader_account,
+            });
+        }
+
+        pub fn cancel_bundle_event(&self, bundle: Bundle, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(BundleCanceled {
+                bundle: bundle.clone(),
+                open_trader_account: bundle.open_trader_account,
+            });
+        }
+
+        pub fn purchase_bundle_event(&self, bundle: Bundle, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(BundlePurchased {
+                bundle: bundle.clone(),
+                open_trader_account: bundle.open_trader_account,
+            });
+        }
+
+        pub fn swap_event(&self, swap: Swap, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(SwapCreated {
+                swap: swap.clone(),
+                open_trader_account: swap.open_trader_account,
+            });
+        }
+
+        pub fn cancel_swap_event(&self, swap: Swap, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(SwapCanceled {
+                swap: swap.clone(),
+                open_trader_account: swap.open_trader_account,
+            });
+        }
+
+        pub fn accept_swap_event(&self, swap: Swap, emitter_badge: Proof) {
+            emitter_badge.check(self.emitter_badge_auth);
+            Runtime::emit_event(SwapAccepted {
+                swap: swap.clone(),
+                open_trader_account: swap.open_trader_account,
+            });
+        }
     }
 }
@@ -8,12 +8,13 @@ use scrypto::prelude::*;
 // and cancel listings.
 
 #[derive(ScryptoSbor, NonFungibleData)]
-struct TraderKey {
+pub struct TraderKey {
     name: String,
     description: String,
     key_image_url: Url,
+    /// The trader account the key opens - cleared when the key is replaced through key recovery
     #[mutable]
-    hub: Option<ComponentAddress>,
+    pub hub: Option<ComponentAddress>,
 }
 
 #[derive(ScryptoSbor, NonFungibleData)]
@@ -39,6 +40,12 @@ mod openhub {
         account_locker: Global<AccountLocker>,
         /// Created accounts
         registered_accounts: KeyValueStore<ComponentAddress, ComponentAddress>,
+        /// The reverse of registered_accounts - the account each trader account was created for
+        registered_traders: KeyValueStore<ComponentAddress, ComponentAddress>,
+        /// The number of trader accounts created
+        total_accounts: u64,
+        /// The current trader key of each trader account - replaced if the key is recovered
+        registered_keys: KeyValueStore<ComponentAddress, NonFungibleGlobalId>,
     }
 
     impl OpenHub {
@@ -129,6 +136,9 @@ mod openhub {
                 component_address,
                 account_locker: locker,
                 registered_accounts: KeyValueStore::new(),
+                registered_traders: KeyValueStore::new(),
+                total_accounts: 0,
+                registered_keys: KeyValueStore::new(),
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -152,7 +162,7 @@ mod openhub {
 
         /// Creates a new open trader account with a emitter badge, personal key, and a badge to hold and trade Royalty NFTs
         pub fn create_open_trader(
-            &self,
+            &mut self,
             my_account: Global<Account>,
         ) -> (NonFungibleGlobalId, Bucket) {
             {
@@ -221,10 +231,79 @@ mod openhub {
             self.registered_accounts
                 .insert(my_account.clone().address(), hub_component_address);
 
+            self.registered_traders
+                .insert(hub_component_address, my_account.address());
+
+            self.registered_keys
+                .insert(hub_component_address, nfgid.clone());
+
+            self.total_accounts += 1;
+
             // return the personal trading account badge (and the nfgid of the account for testing purposes)
             (nfgid, personal_trading_account_badge)
         }
 
+        /// Issues a new trader key for a trader account if the old one has been lost. The caller must prove they own the account
+        /// the trader account was created for, in the same way as a same owner royal transfer. The old key is invalidated - it
+        /// can no longer access the trader account and its hub is cleared, so trader accounts stop accepting it as a private
+        /// buyer or swap counterparty - and the new key becomes the trader account's admin.
+        pub fn recover_trader_key(
+            &mut self,
+            my_account: Global<Account>,
+        ) -> (NonFungibleGlobalId, Bucket) {
+            {
+                // Getting the owner role of the account.
+                let owner_role = my_account.get_owner_role();
+
+                // Assert against it.
+                Runtime::assert_access_rule(owner_role.rule);
+
+                // Assertion passed - the caller is the owner of the account.
+            }
+
+            let hub_component_address = *self
+                .registered_accounts
+                .get(&my_account.address())
+                .expect("[recover_trader_key] Account does not have an OT Trading Hub");
+
+            let old_key = self
+                .registered_keys
+                .get(&hub_component_address)
+                .unwrap()
+                .clone();
+
+            self.open_trader_account_badge.update_non_fungible_data(
+                old_key.local_id(),
+                "hub",
+                None as Option<ComponentAddress>,
+            );
+
+            let personal_trading_account_badge = self
+                .open_trader_account_badge
+                .mint_ruid_non_fungible(TraderKey {
+                    name: "OT Hub Key".to_string(),
+                    description: "Your hub for listing and managing your NFTs across marketplaces and with other users.".to_string(),
+                    key_image_url: Url::of("https://radixopentrade.netlify.app/img/OT_logo_black.webp"),
+                    hub: Some(hub_component_address),
+                });
+
+            let nfgid = NonFungibleGlobalId::new(
+                personal_trading_account_badge.resource_address(),
+                personal_trading_account_badge
+                    .as_non_fungible()
+                    .non_fungible_local_id(),
+            );
+
+            let trader_component: Global<OpenTrader> = hub_component_address.into();
+
+            trader_component.recover_auth_key(nfgid.clone());
+
+            self.registered_keys
+                .insert(hub_component_address, nfgid.clone());
+
+            (nfgid, personal_trading_account_badge)
+        }
+
         pub fn fetch_virt_badge(&mut self) -> ResourceAddress {
             self.emitter_trader_badge.address()
         }
@@ -232,5 +311,42 @@ mod openhub {
         pub fn fetch_royal_nft_depositer_badge(&mut self) -> ResourceAddress {
             self.royal_nft_depositer_badge.address()
         }
+
+        /// Finds the trader account created for an account, so marketplaces can resolve where a user's listings are on-ledger.
+        pub fn get_trader_for_account(
+            &self,
+            account: ComponentAddress,
+        ) -> Option<ComponentAddress> {
+            self.registered_accounts.get(&account).map(|trader| *trader)
+        }
+
+        /// Finds the account a trader account was created for.
+        pub fn get_account_for_trader(
+            &self,
+            trader: ComponentAddress,
+        ) -> Option<ComponentAddress> {
+            self.registered_traders.get(&trader).map(|account| *account)
+        }
+
+        /// Finds the trader account a trader key (hub key) opens.
+        pub fn get_trader_for_key(
+            &self,
+            key_local: NonFungibleLocalId,
+        ) -> Option<ComponentAddress> {
+            if !self
+                .open_trader_account_badge
+                .non_fungible_exists(&key_local)
+            {
+                return None;
+            }
+
+            self.open_trader_account_badge
+                .get_non_fungible_data::<TraderKey>(&key_local)
+                .hub
+        }
+
+        pub fn total_accounts(&self) -> u64 {
+            self.total_accounts
+        }
     }
 }
@@ -1,4 +1,5 @@
 use crate::open_trade_event::event;
+use crate::open_trade_factory::TraderKey;
 use scrypto::component::AccountLocker;
 use scrypto::prelude::*;
 /// This blueprint is a trader account - where they can list items and where items are purchased from. Each method calls the event emitter component.
@@ -23,6 +24,361 @@ pub struct Listing {
     pub nfgid: NonFungibleGlobalId,
     /// trader's account address - helpful for aggregators to know where to fetch listings from.
     pub open_trader_account: ComponentAddress,
+    /// If set, the listing is a Dutch auction and its price falls over time from the start price (also held in price) to the floor price.
+    pub dutch_auction: Option<DutchAuction>,
+    /// Prices in other currencies the listing can also be purchased with (e.g. 100 XRD or 5 USDC).
+    pub alternative_prices: HashMap<ResourceAddress, Decimal>,
+    /// If set, the listing can't be purchased from this time and anyone can return the NFT to the seller with purge_expired.
+    pub expiry: Option<Instant>,
+    /// Specific badges (e.g. a counterparty's OpenTrade hub key) that can purchase the listing for a private deal,
+    /// without the seller needing to mint a new resource to permission a single buyer.
+    pub private_buyers: Vec<NonFungibleGlobalId>,
+    /// The highest marketplace fee rate the seller accepts on this listing - set by the seller when listing.
+    /// A marketplace's fee is read from its badge metadata at the time of purchase, so this stops it being raised after listing.
+    pub max_marketplace_fee: Option<Decimal>,
+}
+
+impl Listing {
+    /// The price the listing can be purchased at right now.
+    pub fn current_price(&self) -> Decimal {
+        match &self.dutch_auction {
+            Some(dutch_auction) => dutch_auction.price_at(Clock::current_time_rounded_to_seconds()),
+            None => self.price,
+        }
+    }
+
+    /// Whether a marketplace or private buyer's proof permits them to purchase the listing.
+    pub fn permits(&self, permission: &Proof, trader_key_resource: ResourceAddress) -> bool {
+        permits_purchase(
+            &self.secondary_seller_permissions,
+            &self.private_buyers,
+            permission,
+            trader_key_resource,
+        )
+    }
+
+    /// Sets the price of the listing in its own currency.
+    pub fn set_price(&mut self, new_price: Decimal) {
+        assert!(
+            self.dutch_auction.is_none(),
+            "[change_price] The price of a Dutch auction listing can not be changed"
+        );
+
+        assert!(
+            new_price > Decimal::zero(),
+            "[change_price] Listing price must be greater than zero"
+        );
+
+        self.price = new_price;
+    }
+
+    /// Adds (or updates) a price in another currency the listing can be purchased with. Dutch auction listings
+    /// can't have alternative prices as only their price in the listing's currency falls over time.
+    pub fn set_alternative_price(&mut self, currency: ResourceAddress, price: Decimal) {
+        assert!(
+            self.dutch_auction.is_none(),
+            "[set_alternative_price] A Dutch auction listing can not have alternative prices"
+        );
+
+        assert!(
+            currency != self.currency,
+            "[set_alternative_price] Currency is already the listing's currency"
+        );
+
+        assert!(
+            price > Decimal::zero(),
+            "[set_alternative_price] Listing price must be greater than zero"
+        );
+
+        self.alternative_prices.insert(currency, price);
+    }
+
+    /// Whether the listing has passed its expiry time.
+    pub fn is_expired(&self) -> bool {
+        self.expiry
+            .is_some_and(|expiry| Clock::current_time_is_at_or_after(expiry, TimePrecision::Second))
+    }
+
+    /// The price the listing can be purchased at right now in a given currency.
+    pub fn current_price_in(&self, currency: ResourceAddress) -> Decimal {
+        if currency == self.currency {
+            return self.current_price();
+        }
+
+        *self
+            .alternative_prices
+            .get(&currency)
+            .expect("[purchase] Payment currency is not accepted for this listing")
+    }
+}
+
+/// How the price of a Dutch auction listing falls from its start price to its floor price.
+#[derive(ScryptoSbor, Clone)]
+pub enum PriceDecay {
+    /// The price falls continuously every second
+    Linear,
+    /// The price falls in steps at the end of every interval of this many seconds
+    Stepwise(i64),
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct DutchAuction {
+    /// The price the listing starts at
+    pub start_price: Decimal,
+    /// The lowest price the listing falls to
+    pub floor_price: Decimal,
+    /// The time the price starts to fall
+    pub start_time: Instant,
+    /// The time the price reaches the floor price
+    pub end_time: Instant,
+    pub decay: PriceDecay,
+}
+
+impl DutchAuction {
+    /// The price of the listing at a given time.
+    pub fn price_at(&self, time: Instant) -> Decimal {
+        let start = self.start_time.seconds_since_unix_epoch;
+        let end = self.end_time.seconds_since_unix_epoch;
+        let now = time.seconds_since_unix_epoch;
+
+        if now <= start {
+            return self.start_price;
+        }
+
+        if now >= end {
+            return self.floor_price;
+        }
+
+        let mut elapsed = now - start;
+
+        if let PriceDecay::Stepwise(step_seconds) = self.decay {
+            elapsed -= elapsed % step_seconds;
+        }
+
+        let price_drop = self
+            .start_price
+            .checked_sub(self.floor_price)
+            .unwrap()
+            .checked_mul(Decimal::from(elapsed))
+            .unwrap()
+            .checked_div(Decimal::from(end - start))
+            .unwrap();
+
+        self.start_price.checked_sub(price_drop).unwrap()
+    }
+}
+
+/// Whether a marketplace or private buyer's proof permits them to purchase a listing or bundle - either the proof's resource
+/// is a permitted marketplace, or the proof contains the exact badge of a permitted private buyer. A private buyer's
+/// trader key no longer permits a purchase once it has been replaced through key recovery.
+fn permits_purchase(
+    permissions: &[ResourceAddress],
+    private_buyers: &[NonFungibleGlobalId],
+    permission: &Proof,
+    trader_key_resource: ResourceAddress,
+) -> bool {
+    let permission_resource = permission.resource_address();
+
+    if permissions.contains(&permission_resource) {
+        return true;
+    }
+
+    if !private_buyers
+        .iter()
+        .any(|buyer| buyer.resource_address() == permission_resource)
+    {
+        return false;
+    }
+
+    permission
+        .clone()
+        .skip_checking()
+        .as_non_fungible()
+        .non_fungible_local_ids()
+        .into_iter()
+        .any(|local_id| {
+            let buyer = NonFungibleGlobalId::new(permission_resource, local_id);
+
+            private_buyers.contains(&buyer) && !is_recovered_trader_key(&buyer, trader_key_resource)
+        })
+}
+
+/// Whether a badge is an OpenTrade trader key that has been replaced through key recovery. Recovery clears the old key's
+/// hub, so a lost key can't still be used as a private buyer or a swap counterparty.
+fn is_recovered_trader_key(
+    badge: &NonFungibleGlobalId,
+    trader_key_resource: ResourceAddress,
+) -> bool {
+    badge.resource_address() == trader_key_resource
+        && ResourceManager::from_address(trader_key_resource)
+            .get_non_fungible_data::<TraderKey>(badge.local_id())
+            .hub
+            .is_none()
+}
+
+/// What happened to the listings in a batched listing event.
+#[derive(ScryptoSbor, Clone)]
+pub enum ListingBatchAction {
+    Created,
+    Updated,
+    Canceled,
+}
+
+/// What an offer is made on - either one specific NFT or any NFT from a collection.
+#[derive(ScryptoSbor, Clone, PartialEq, Eq)]
+pub enum OfferTarget {
+    Nft(NonFungibleGlobalId),
+    Collection(ResourceAddress),
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct Offer {
+    /// The id of the offer in the buyer's trader account. A seller provides this id to accept the offer.
+    pub offer_id: u64,
+    /// The NFTs and/or collections the escrowed tokens are offered on, with the price offered for each.
+    /// An offer can only be filled once - as soon as one target is accepted the offer closes for all the others
+    /// and any of the escrow not spent is returned to the buyer.
+    pub targets: Vec<(OfferTarget, Decimal)>,
+    /// The currency the offer is made in (e.g. XRD, FLOOP, EARLY, HUG)
+    pub currency: ResourceAddress,
+    /// The amount of tokens the buyer has escrowed in their trader account to back the offer.
+    pub escrowed_amount: Decimal,
+    /// buyer's trader account address - helpful for aggregators to know where to fetch offers from.
+    pub open_trader_account: ComponentAddress,
+    /// The time after which the offer can no longer be accepted. The escrow stays in the trader account until the offer is cancelled.
+    pub expiry: Option<Instant>,
+}
+
+impl Offer {
+    /// Whether the offer has passed its expiry time.
+    pub fn is_expired(&self) -> bool {
+        self.expiry
+            .is_some_and(|expiry| Clock::current_time_is_at_or_after(expiry, TimePrecision::Second))
+    }
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct Auction {
+    /// The permissions that a marketplace or private buyer must have to bid on the auction.
+    pub secondary_seller_permissions: Vec<ResourceAddress>,
+    /// The currency bids must be placed in
+    pub currency: ResourceAddress,
+    /// The lowest amount the first bid can be
+    pub reserve_price: Decimal,
+    /// The amount each new bid must beat the current highest bid by
+    pub minimum_increment: Decimal,
+    /// Bids can't be placed before the start time
+    pub start_time: Instant,
+    /// Bids can't be placed after the end time - the auction can then be settled
+    pub end_time: Instant,
+    /// A bid placed within this many seconds of the end time extends the auction to this many seconds from the bid
+    pub extension_seconds: i64,
+    /// The current highest bid, which is held in escrow in the trader account
+    pub highest_bid: Decimal,
+    /// The account the NFT is sent to if the highest bid wins - also where a bid is refunded when outbid
+    pub highest_bidder: Option<Global<Account>>,
+    /// The permission the highest bid was placed with - used as the buyer when paying royalties at settlement
+    pub highest_bid_permission: Option<ResourceAddress>,
+    pub nfgid: NonFungibleGlobalId,
+    /// trader's account address - helpful for aggregators to know where to fetch auctions from.
+    pub open_trader_account: ComponentAddress,
+}
+
+/// When bids can be placed on an auction.
+#[derive(ScryptoSbor, Clone)]
+pub struct AuctionTiming {
+    /// Bids can't be placed before the start time
+    pub start_time: Instant,
+    /// Bids can't be placed after the end time
+    pub end_time: Instant,
+    /// A bid placed within this many seconds of the end time extends the auction to this many seconds from the bid
+    pub extension_seconds: i64,
+}
+
+/// The terms of one listing in a batch listing.
+#[derive(ScryptoSbor, Clone)]
+pub struct ListingTerms {
+    pub currency: ResourceAddress,
+    pub price: Decimal,
+    pub permissions: Vec<ResourceAddress>,
+    /// Prices in other currencies the listing can also be purchased with.
+    pub alternative_prices: HashMap<ResourceAddress, Decimal>,
+    /// The highest marketplace fee rate the seller accepts on the listing.
+    pub max_marketplace_fee: Option<Decimal>,
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct Bundle {
+    /// The id of the bundle in the trader account. A buyer provides this id to purchase the bundle.
+    pub bundle_id: u64,
+    /// The permissions that a marketplace or private buyer must have to purchase the bundle.
+    pub secondary_seller_permissions: Vec<ResourceAddress>,
+    /// The currency the bundle is listed in (e.g. XRD, FLOOP, EARLY, HUG)
+    pub currency: ResourceAddress,
+    /// The price of the whole bundle
+    pub price: Decimal,
+    /// The NFTs in the bundle with the portion of the price each is sold for. Each royalty enforced NFT
+    /// pays its royalty on its own portion.
+    pub items: Vec<(NonFungibleGlobalId, Decimal)>,
+    /// trader's account address - helpful for aggregators to know where to fetch bundles from.
+    pub open_trader_account: ComponentAddress,
+    /// The highest marketplace fee rate the seller accepts on the bundle.
+    pub max_marketplace_fee: Option<Decimal>,
+    /// Specific badges (e.g. a buyer's OpenTrade trader key) permitted to purchase the bundle as a private deal.
+    pub private_buyers: Vec<NonFungibleGlobalId>,
+}
+
+impl Bundle {
+    /// The NFTs in the bundle.
+    pub fn nfgids(&self) -> Vec<NonFungibleGlobalId> {
+        self.items.iter().map(|(nfgid, _)| nfgid.clone()).collect()
+    }
+
+    /// Whether a marketplace or private buyer's proof permits them to purchase the bundle.
+    pub fn permits(&self, permission: &Proof, trader_key_resource: ResourceAddress) -> bool {
+        permits_purchase(
+            &self.secondary_seller_permissions,
+            &self.private_buyers,
+            permission,
+            trader_key_resource,
+        )
+    }
+}
+
+/// An asset on either side of a swap - an amount of a fungible token or one specific NFT.
+#[derive(ScryptoSbor, Clone, PartialEq, Eq)]
+pub enum SwapAsset {
+    Fungible(ResourceAddress, Decimal),
+    Nft(NonFungibleGlobalId),
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct Swap {
+    /// The id of the swap in the proposer's trader account. The counterparty provides this id to accept the swap.
+    pub swap_id: u64,
+    /// The assets the proposer has escrowed in their trader account.
+    pub offered: Vec<SwapAsset>,
+    /// The assets the proposer wants in return - the counterparty must provide exactly these to accept.
+    pub requested: Vec<SwapAsset>,
+    /// If set, only the holder of this badge (e.g. the counterparty's OpenTrade trader key) can accept the swap.
+    pub counterparty: Option<NonFungibleGlobalId>,
+    /// Tokens the proposer has escrowed to pay the swap royalties of the royalty NFTs they're offering.
+    pub royalty_deposit: Option<(ResourceAddress, Decimal)>,
+    /// proposer's trader account address - helpful for aggregators to know where to fetch swaps from.
+    pub open_trader_account: ComponentAddress,
+}
+
+impl Swap {
+    /// The NFTs the proposer has escrowed in the swap.
+    pub fn offered_nfts(&self) -> Vec<NonFungibleGlobalId> {
+        self.offered
+            .iter()
+            .filter_map(|asset| match asset {
+                SwapAsset::Nft(nfgid) => Some(nfgid.clone()),
+                SwapAsset::Fungible(..) => None,
+            })
+            .collect()
+    }
 }
 
 // To Do: register types for the Listing struct and in other blueprints
@@ -31,27 +387,73 @@ mod opentrader {
 
     enable_method_auth! {
     roles {
-        admin => updatable_by: [];
+        admin => updatable_by: [SELF];
+        hub => updatable_by: [];
     },
     methods {
         list => restrict_to: [admin];
+        dutch_list => restrict_to: [admin];
         royal_list => restrict_to: [admin];
         same_owner_royal_transfer => restrict_to: [admin];
         transfer_royal_nft_to_component => restrict_to: [admin];
         revoke_market_permission => restrict_to: [admin];
         add_buyer_permission => restrict_to: [admin];
+        add_private_buyer => restrict_to: [admin];
+        revoke_private_buyer => restrict_to: [admin];
         change_price => restrict_to: [admin];
+        batch_list => restrict_to: [admin];
+        batch_change_price => restrict_to: [admin];
+        batch_cancel_listings => restrict_to: [admin];
+        set_alternative_price => restrict_to: [admin];
+        remove_alternative_price => restrict_to: [admin];
+        set_listing_expiry => restrict_to: [admin];
+        purge_expired => PUBLIC;
         cancel_listing => restrict_to: [admin];
         cancel_royal_listing => restrict_to: [admin];
+        create_listed_nft_proofs => restrict_to: [admin];
+        make_offer => restrict_to: [admin];
+        set_offer_expiry => restrict_to: [admin];
+        cancel_offer => restrict_to: [admin];
+        accept_offer_with_listing => restrict_to: [admin];
+        auction_list => restrict_to: [admin];
+        cancel_auction => restrict_to: [admin];
+        bundle_list => restrict_to: [admin];
+        cancel_bundle => restrict_to: [admin];
+        add_bundle_private_buyer => restrict_to: [admin];
+        revoke_bundle_private_buyer => restrict_to: [admin];
+        purchase_bundle => PUBLIC;
+        propose_swap => restrict_to: [admin];
+        cancel_swap => restrict_to: [admin];
+        accept_swap => PUBLIC;
+        place_bid => PUBLIC;
+        settle_auction => PUBLIC;
+        claim_auction_nft => PUBLIC;
         purchase_royal_listing => PUBLIC;
+        purchase_royal_listing_with_fee_rate => PUBLIC;
         purchase_listing => PUBLIC;
+        purchase_listing_with_fee_rate => PUBLIC;
+        accept_offer => PUBLIC;
         fetch_auth_key => PUBLIC;
+        set_max_marketplace_fee => restrict_to: [admin];
+        set_listing_max_marketplace_fee => restrict_to: [admin];
+        recover_auth_key => restrict_to: [hub];
+        get_listing => PUBLIC;
+        get_listings => PUBLIC;
+        listing_count => PUBLIC;
+        is_listed => PUBLIC;
     }
     }
 
     struct OpenTrader {
         /// The key value store of listings information for NFTs the user has listed for sale.
         listings: KeyValueStore<NonFungibleGlobalId, Listing>,
+        /// An index of the listings by position, so listings can be paged through without scanning the listings key value store.
+        /// Removing a listing moves the last listing into its position, so the order isn't stable.
+        listing_index: KeyValueStore<u64, NonFungibleGlobalId>,
+        /// The position of each listing in the listing index.
+        listing_positions: KeyValueStore<NonFungibleGlobalId, u64>,
+        /// The number of active listings.
+        listing_count: u64,
         /// The key value store of vaults that store all the NFTs that the user has listed for sale.
         nft_vaults: KeyValueStore<NonFungibleGlobalId, Vault>,
         /// The key value store of vaults that store all the revenue the user has made from sales.
@@ -80,6 +482,32 @@ mod opentrader {
         trader_account_component_address: ComponentAddress,
         /// This kvs tracks the royal listing transactions made on the account, preventing double method calls for royalty NFTs.
         transactions: KeyValueStore<Hash, ()>,
+        /// The key value store of offers the user has made on other traders' NFTs.
+        offers: KeyValueStore<u64, Offer>,
+        /// The vaults that escrow the tokens backing the user's offers - one vault per currency shared by all offers.
+        offer_escrow: KeyValueStore<ResourceAddress, Vault>,
+        /// The id given to the next offer made from this account.
+        offer_id_counter: u64,
+        /// The key value store of auctions for NFTs the user has listed in an auction. The NFTs are held in the nft_vaults.
+        auctions: KeyValueStore<NonFungibleGlobalId, Auction>,
+        /// The vaults that escrow the highest bids of the user's auctions - one vault per currency shared by all auctions.
+        auction_escrow: KeyValueStore<ResourceAddress, Vault>,
+        /// Royalty NFTs won at auction that are waiting for their winner to claim them, with the winning account.
+        /// The NFTs stay in the nft_vaults until claimed.
+        unclaimed_auction_nfts: KeyValueStore<NonFungibleGlobalId, Global<Account>>,
+        /// The key value store of bundles of NFTs the user has listed to be sold together. The NFTs are held in the nft_vaults.
+        bundles: KeyValueStore<u64, Bundle>,
+        /// The id given to the next bundle listed from this account.
+        bundle_id_counter: u64,
+        /// The key value store of swaps the user has proposed.
+        swaps: KeyValueStore<u64, Swap>,
+        /// The vaults that escrow the assets and royalty deposits of the user's swaps - one vault per resource shared by all swaps.
+        swap_escrow: KeyValueStore<ResourceAddress, Vault>,
+        /// The id given to the next swap proposed from this account.
+        swap_id_counter: u64,
+        /// The highest marketplace fee rate the user accepts on their sales. Marketplace fees are read from the marketplace's badge
+        /// metadata at the time of purchase, so this stops a marketplace raising its fee to drain a sale.
+        max_marketplace_fee: Option<Decimal>,
     }
 
     impl OpenTrader {
@@ -106,6 +534,9 @@ mod opentrader {
                 auth_key_local,
                 auth_key_resource,
                 listings: KeyValueStore::new(),
+                listing_index: KeyValueStore::new(),
+                listing_positions: KeyValueStore::new(),
+                listing_count: 0,
                 account_locker: locker,
                 my_account,
                 emitter_badge: Vault::with_bucket(emitter_badge),
@@ -116,6 +547,18 @@ mod opentrader {
                 sales_revenue: KeyValueStore::new(),
                 royal_admin: Vault::with_bucket(depositer_admin),
                 transactions: KeyValueStore::new(),
+                offers: KeyValueStore::new(),
+                offer_escrow: KeyValueStore::new(),
+                offer_id_counter: 0,
+                auctions: KeyValueStore::new(),
+                auction_escrow: KeyValueStore::new(),
+                unclaimed_auction_nfts: KeyValueStore::new(),
+                bundles: KeyValueStore::new(),
+                bundle_id_counter: 0,
+                swaps: KeyValueStore::new(),
+                swap_escrow: KeyValueStore::new(),
+                swap_id_counter: 0,
+                max_marketplace_fee: None,
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -135,6 +578,7 @@ mod opentrader {
             ))
             .roles(roles!(
                 admin => rule!(require(auth_key));
+                hub => rule!(require(global_caller(dapp_global)));
             ))
             .with_address(trader_address_reservation)
             .globalize()
@@ -158,6 +602,8 @@ mod opentrader {
             // The permissions that a secondary seller must have to sell an NFT. This is used to ensure that only selected
             // marketplaces or private buyers can buy an NFT.
             permissions: Vec<ResourceAddress>,
+            // The highest marketplace fee rate the seller accepts on the listing
+            max_marketplace_fee: Option<Decimal>,
             // The badge that is used to authenticate the user listing the NFT
             // trader_badge: Proof,
         ) {
@@ -169,6 +615,8 @@ mod opentrader {
                 "[list_nft] Listing price must be greater than zero"
             );
 
+            Self::assert_max_marketplace_fee_in_range(max_marketplace_fee, "royal_list");
+
             assert!(
                 nft_to_list.amount() == dec!(1),
                 "[list_nft] Only one NFT can be listed at a time"
@@ -198,12 +646,17 @@ mod opentrader {
                 price,
                 nfgid: nfgid.clone(),
                 open_trader_account,
+                dutch_auction: None,
+                alternative_prices: HashMap::new(),
+                expiry: None,
+                private_buyers: vec![],
+                max_marketplace_fee,
             };
 
             // add the listing information. We don't need to worry about
             // duplicating as a listing key entry is always removed when and NFT is sold
             // or if the listing is cancelled.
-            self.listings.insert(nfgid.clone(), new_listing.clone());
+            self.insert_listing(nfgid.clone(), new_listing.clone());
 
             // As this is a royalty enforced listing, we need to use the royalty admin badge
             // to authenticate the deposit of the NFT.
@@ -236,6 +689,7 @@ mod opentrader {
         /// The intention is that in the majority of cases, a marketplace would call this method using their
         /// marketplace badge to authenticate the purchase, get the NFT and return it to the user on their platform.
         /// However, for a private deal, a user could call this method directly with a badge issued by the listing creator for this deal.
+        /// Returns the marketplace fee, the purchase receipt and any change from a payment above the listing price.
         pub fn purchase_royal_listing(
             &mut self,
             // The NFGID of the NFT to purchase
@@ -245,8 +699,39 @@ mod opentrader {
             // The badge of the marketplace or private buyer that is purchasing the NFT
             permission: Proof,
             // The account that the NFT should be sent to
+            account_recipient: Global<Account>,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            self.purchase_royal_listing_at(nfgid, payment, permission, account_recipient, None)
+        }
+
+        /// Purchases a royal listing at a fee rate the marketplace has scheduled for this purchase (e.g. a collection fee,
+        /// volume tier or promotion) rather than the fee published on its badge. The published fee stays the ceiling, so a
+        /// scheduled rate can only discount it.
+        pub fn purchase_royal_listing_with_fee_rate(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            permission: Proof,
+            account_recipient: Global<Account>,
+            marketplace_fee_rate: Decimal,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            self.purchase_royal_listing_at(
+                nfgid,
+                payment,
+                permission,
+                account_recipient,
+                Some(marketplace_fee_rate),
+            )
+        }
+
+        fn purchase_royal_listing_at(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            mut payment: FungibleBucket,
+            permission: Proof,
             mut account_recipient: Global<Account>,
-        ) -> (Vec<Bucket>, Vec<Bucket>) {
+            scheduled_fee_rate: Option<Decimal>,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
             // fee payment is tuple 1, receipt is tuple 2
             let mut tuple_buckets: (Vec<Bucket>, Vec<Bucket>) = (vec![], vec![]);
             let listing_event: Listing;
@@ -254,35 +739,50 @@ mod opentrader {
             let (_nft_resource, nft_local) = nfgid.clone().into_parts();
 
             // First authenticate the proof to check that the marketplace or private buyer has the correct permissions to purchase the NFT
-            // We are just using a resource address as validation here - however this could be a more complex check in the future for local ids
-            // so that for private deals a brand new resource doesn't need to be created.
+            // A marketplace is validated by its badge's resource address, while a private buyer is validated by the exact badge
+            // (resource address and local id) the seller targeted the deal at - so a brand new resource doesn't need to be created.
 
             let trading_permission = permission.resource_address();
 
-            {
+            let change = {
                 let listing_permission = self
                     .listings
                     .get(&nfgid)
                     .expect("[purchase] Listing not found");
 
                 assert!(
-                    listing_permission
-                        .secondary_seller_permissions
-                        .contains(&trading_permission),
+                    listing_permission.permits(&permission, self.auth_key_resource),
                     "[purchase] Marketplace does not have permission to purchase this listing"
                 );
-            }
 
-            // We get the marketplace fee rate from the metadata of the proof
+                assert!(
+                    !listing_permission.is_expired(),
+                    "[purchase] Listing has expired"
+                );
+
+                Self::take_change(&listing_permission, &mut payment)
+            };
+
+            // We get the marketplace fee rate from the metadata of the proof, unless the marketplace has scheduled a lower rate
             // We calculate the marketplace fee from the payment amount.
             // This could be an unsafe decimal at this point - however when taking from the payment we use a safe rounding mode.
             // If not marketplace fee is set, we set the rate to 0.
 
-            let marketplace_fee_option: Option<Decimal> = permission
-                .skip_checking()
-                .resource_manager()
-                .get_metadata("marketplace_fee")
-                .unwrap();
+            let listing_max_marketplace_fee = self
+                .listings
+                .get(&nfgid)
+                .expect("[purchase] Listing not found")
+                .max_marketplace_fee;
+
+            let marketplace_fee_option: Option<Decimal> =
+                Self::marketplace_fee_rate(permission.resource_address(), scheduled_fee_rate);
+
+            if let Some(marketplace_fee_rate) = marketplace_fee_option {
+                self.assert_marketplace_fee_allowed(
+                    marketplace_fee_rate,
+                    listing_max_marketplace_fee,
+                );
+            }
 
             let marketplace_fee_rate: Decimal;
             let marketplace_fee: Decimal;
@@ -302,20 +802,13 @@ mod opentrader {
 
                 listing_event = listing.clone();
 
-                let price = listing.price;
+                let price = listing.current_price_in(payment.resource_address());
 
                 assert!(
                     payment.amount() == price,
                     "[purchase] Payment amount does not match listing price"
                 );
 
-                let currency = listing.currency;
-
-                assert!(
-                    payment.resource_address() == currency,
-                    "[purchase] Payment currency does not match listing currency",
-                );
-
                 // As mentioned elsewhere - we want to ensure no one can do an atomic transaction of listing and purchasing a Royalty NFT
                 // as this would provide a loophole for trading NFTs without paying royalties. We do this by checking the hash of the listing
                 // and the hash of the purchase. If they are the same, we abort the transaction.
@@ -395,12 +888,17 @@ mod opentrader {
                 ));
 
                 // We send the full payment to the royalty component so that it can take its %fee.
+                // The NFT's global id and sale price are given so the creator can set royalties per NFT.
                 // We also provide the trading permission to check against any other permissions the creator has set.
-                let mut remainder_after_royalty: Bucket =
-                    Global::<AnyComponent>::from(call_address).call_raw(
-                        "pay_royalty",
-                        scrypto_args!(nft_address, payment, trading_permission),
-                    );
+                let sale_price = payment.amount();
+
+                let mut remainder_after_royalty: Bucket = Self::pay_royalty(
+                    call_address,
+                    nfgid.clone(),
+                    sale_price,
+                    payment.into(),
+                    trading_permission,
+                );
 
                 // we then take the marketplaces fee (we've already calculated this earlier based on the full payment amount).
 
@@ -448,7 +946,7 @@ mod opentrader {
                     account_recipient.try_deposit_or_abort(nft.into(), None);
                 });
             }
-            self.listings.remove(&nfgid);
+            self.remove_sold_listing(&nfgid);
 
             // finally we emit a listing event via the event emitter component
             let emitter_proof = self
@@ -459,7 +957,11 @@ mod opentrader {
             self.event_manager
                 .purchase_listing_event(listing_event, nfgid, emitter_proof.into());
 
-            tuple_buckets
+            (
+                tuple_buckets.0,
+                tuple_buckets.1,
+                change.map(|change| change.into()),
+            )
         }
 
         pub fn cancel_royal_listing(&mut self, nfgid: NonFungibleGlobalId) {
@@ -491,7 +993,7 @@ mod opentrader {
                 );
             }
 
-            self.listings.remove(&nfgid);
+            self.unindex_listing(&nfgid);
 
             self.royal_admin.as_fungible().authorize_with_amount(1, || {
                 self.my_account
@@ -594,6 +1096,7 @@ mod opentrader {
             currency: ResourceAddress,
             price: Decimal,
             permissions: Vec<ResourceAddress>,
+            max_marketplace_fee: Option<Decimal>,
         ) {
             assert!(!nft_bucket.is_empty(), "[list_nft] No NFT provided");
 
@@ -602,6 +1105,8 @@ mod opentrader {
                 "[list_nft] Listing price must be greater than zero"
             );
 
+            Self::assert_max_marketplace_fee_in_range(max_marketplace_fee, "list");
+
             assert!(
                 nft_bucket.amount() == dec!(1),
                 "[list_nft] Only one NFT can be listed at a time"
@@ -620,6 +1125,11 @@ mod opentrader {
                 price,
                 nfgid: nfgid.clone(),
                 open_trader_account,
+                dutch_auction: None,
+                alternative_prices: HashMap::new(),
+                expiry: None,
+                private_buyers: vec![],
+                max_marketplace_fee,
             };
 
             let vault_exists = self.nft_vaults.get(&nfgid).is_some();
@@ -638,7 +1148,7 @@ mod opentrader {
             // self.nft_vaults
             //     .insert(nfgid.clone(), Vault::with_bucket(nft_bucket.into()));
 
-            self.listings.insert(nfgid.clone(), new_listing.clone());
+            self.insert_listing(nfgid.clone(), new_listing.clone());
 
             // finally we emit a listing event via the event emitter component
             let emitter_proof = self
@@ -650,6 +1160,97 @@ mod opentrader {
                 .listing_event(new_listing, nfgid, emitter_proof.into());
         }
 
+        /// Lists an NFT (royalty enforced or standard) in a Dutch auction, where the price falls from the start price to the floor price
+        /// between the start and end time. The listing is purchased through the usual purchase_listing/purchase_royal_listing methods
+        /// at the price at the time of purchase - a buyer can pay more than the current price and receives the difference back.
+        pub fn dutch_list(
+            &mut self,
+            nft_to_list: Bucket,
+            currency: ResourceAddress,
+            dutch_auction: DutchAuction,
+            permissions: Vec<ResourceAddress>,
+            max_marketplace_fee: Option<Decimal>,
+        ) {
+            assert!(
+                nft_to_list.amount() == dec!(1),
+                "[dutch_list] Only one NFT can be listed at a time"
+            );
+
+            Self::assert_max_marketplace_fee_in_range(max_marketplace_fee, "dutch_list");
+
+            assert!(
+                dutch_auction.floor_price > Decimal::zero(),
+                "[dutch_list] Floor price must be greater than zero"
+            );
+
+            assert!(
+                dutch_auction.start_price >= dutch_auction.floor_price,
+                "[dutch_list] Start price must not be below the floor price"
+            );
+
+            assert!(
+                dutch_auction
+                    .start_time
+                    .compare(dutch_auction.end_time, TimeComparisonOperator::Lt),
+                "[dutch_list] Price decay must start before it ends"
+            );
+
+            if let PriceDecay::Stepwise(step_seconds) = dutch_auction.decay {
+                assert!(
+                    step_seconds > 0,
+                    "[dutch_list] Price steps must be at least one second long"
+                );
+            }
+
+            let nfgid = NonFungibleGlobalId::new(
+                nft_to_list.resource_address(),
+                nft_to_list.as_non_fungible().non_fungible_local_id(),
+            );
+
+            // As with royal listings, we block a Dutch auction listing being listed and purchased in the same transaction.
+            let transaction_hash = Runtime::transaction_hash();
+
+            self.transactions.insert(transaction_hash, ());
+
+            let new_listing = Listing {
+                secondary_seller_permissions: permissions,
+                currency,
+                price: dutch_auction.start_price,
+                nfgid: nfgid.clone(),
+                open_trader_account: self.trader_account_component_address,
+                dutch_auction: Some(dutch_auction),
+                alternative_prices: HashMap::new(),
+                expiry: None,
+                private_buyers: vec![],
+                max_marketplace_fee,
+            };
+
+            self.insert_listing(nfgid.clone(), new_listing.clone());
+
+            // Royalty NFTs need the royalty admin badge to be deposited - this is harmless for standard NFTs.
+            self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                let vault_exists = self.nft_vaults.get(&nfgid).is_some();
+
+                if vault_exists {
+                    self.nft_vaults
+                        .get_mut(&nfgid)
+                        .expect("[dutch_list] NFT not found")
+                        .put(nft_to_list);
+                } else {
+                    self.nft_vaults
+                        .insert(nfgid.clone(), Vault::with_bucket(nft_to_list));
+                }
+            });
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .listing_event(new_listing, nfgid, emitter_proof.into());
+        }
+
         pub fn revoke_market_permission(
             &mut self,
             nft_id: NonFungibleGlobalId,
@@ -699,7 +1300,8 @@ mod opentrader {
                 .listings
                 .get_mut(&nft_id)
                 .expect("[change_price] Listing not found");
-            listing.price = new_price;
+
+            listing.set_price(new_price);
 
             let emitter_proof = self
                 .emitter_badge
@@ -710,76 +1312,327 @@ mod opentrader {
                 .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
         }
 
-        pub fn cancel_listing(&mut self, nft_id: NonFungibleGlobalId) -> Vec<Bucket> {
-            let mut nft_bucket: Vec<Bucket> = vec![];
-
-            {
-                let mut nft = self
-                    .nft_vaults
-                    .get_mut(&nft_id)
-                    .expect("[cancel] NFT not found");
-
-                nft_bucket.push(nft.take_all());
-            }
-            {
-                let listing = self
-                    .listings
-                    .get(&nft_id)
-                    .expect("[change_price] Listing not found");
-
-                let emitter_proof = self
-                    .emitter_badge
-                    .as_non_fungible()
-                    .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+        /// Allows a listing to also be purchased in another currency at the given price (e.g. 100 XRD or 5 USDC),
+        /// or updates its price in that currency.
+        pub fn set_alternative_price(
+            &mut self,
+            nft_id: NonFungibleGlobalId,
+            currency: ResourceAddress,
+            price: Decimal,
+        ) {
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[set_alternative_price] Listing not found");
 
-                self.event_manager.cancel_listing_event(
-                    listing.clone(),
-                    nft_id.clone(),
-                    emitter_proof.into(),
-                );
-            }
+            listing.set_alternative_price(currency, price);
 
-            self.listings.remove(&nft_id);
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
 
-            nft_bucket
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
         }
 
-        pub fn purchase_listing(
+        /// Permits a specific badge to purchase a listing, so a private deal can be targeted at a single buyer
+        /// (e.g. their OpenTrade hub key) without minting a new resource for the deal.
+        pub fn add_private_buyer(
             &mut self,
-            nfgid: NonFungibleGlobalId,
-            mut payment: FungibleBucket,
-            permission: Proof,
-        ) -> (Vec<Bucket>, Vec<Bucket>) {
-            let (_nft_resource, nft_local) = nfgid.clone().into_parts();
+            nft_id: NonFungibleGlobalId,
+            buyer: NonFungibleGlobalId,
+        ) {
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[add_private_buyer] Listing not found");
 
-            let mut return_buckets: (Vec<Bucket>, Vec<Bucket>) = (vec![], vec![]);
-            let listing_event: Listing;
+            listing.private_buyers.push(buyer);
 
-            {
-                let marketplace = permission.resource_address();
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
 
-                let listing_permission = self
-                    .listings
-                    .get(&nfgid)
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
+        }
+
+        pub fn revoke_private_buyer(
+            &mut self,
+            nft_id: NonFungibleGlobalId,
+            buyer: NonFungibleGlobalId,
+        ) {
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[revoke_private_buyer] Listing not found");
+
+            listing
+                .private_buyers
+                .retain(|private_buyer| private_buyer != &buyer);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
+        }
+
+        /// Sets or removes the highest marketplace fee rate (e.g. 0.05 = 5%) the user accepts on any of their sales.
+        pub fn set_max_marketplace_fee(&mut self, max_marketplace_fee: Option<Decimal>) {
+            Self::assert_max_marketplace_fee_in_range(
+                max_marketplace_fee,
+                "set_max_marketplace_fee",
+            );
+
+            self.max_marketplace_fee = max_marketplace_fee;
+        }
+
+        /// Sets or removes the highest marketplace fee rate the user accepts on one listing.
+        pub fn set_listing_max_marketplace_fee(
+            &mut self,
+            nft_id: NonFungibleGlobalId,
+            max_marketplace_fee: Option<Decimal>,
+        ) {
+            Self::assert_max_marketplace_fee_in_range(
+                max_marketplace_fee,
+                "set_listing_max_marketplace_fee",
+            );
+
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[set_listing_max_marketplace_fee] Listing not found");
+
+            listing.max_marketplace_fee = max_marketplace_fee;
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
+        }
+
+        /// Sets or removes the time a listing expires. Once expired a listing can't be purchased and anyone can return the NFT
+        /// to the user's account with purge_expired.
+        pub fn set_listing_expiry(&mut self, nft_id: NonFungibleGlobalId, expiry: Option<Instant>) {
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[set_listing_expiry] Listing not found");
+
+            listing.expiry = expiry;
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
+        }
+
+        /// Returns the NFTs of expired listings to the user's account. Anyone can call this so that stale listings
+        /// don't linger - listings that haven't expired are rejected.
+        pub fn purge_expired(&mut self, nft_ids: Vec<NonFungibleGlobalId>) {
+            for nft_id in nft_ids {
+                let (_nft_resource, nft_local) = nft_id.clone().into_parts();
+
+                let listing = self
+                    .unindex_listing(&nft_id)
+                    .expect("[purge_expired] Listing not found");
+
+                assert!(
+                    listing.is_expired(),
+                    "[purge_expired] Listing has not expired"
+                );
+
+                let nft: Bucket = self
+                    .nft_vaults
+                    .get_mut(&nft_id)
+                    .expect("[purge_expired] NFT not found")
+                    .as_non_fungible()
+                    .take_non_fungible(&nft_local)
+                    .into();
+
+                self.deliver_nft(nft, self.my_account);
+
+                let emitter_proof = self
+                    .emitter_badge
+                    .as_non_fungible()
+                    .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+                self.event_manager
+                    .expire_listing_event(listing, nft_id, emitter_proof.into());
+            }
+        }
+
+        /// Stops a listing being purchasable in one of its alternative currencies.
+        pub fn remove_alternative_price(
+            &mut self,
+            nft_id: NonFungibleGlobalId,
+            currency: ResourceAddress,
+        ) {
+            let mut listing = self
+                .listings
+                .get_mut(&nft_id)
+                .expect("[remove_alternative_price] Listing not found");
+
+            listing
+                .alternative_prices
+                .remove(&currency)
+                .expect("[remove_alternative_price] Listing has no price in this currency");
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .update_listing_event(listing.clone(), nft_id, emitter_proof.into());
+        }
+
+        pub fn cancel_listing(&mut self, nft_id: NonFungibleGlobalId) -> Vec<Bucket> {
+            let mut nft_bucket: Vec<Bucket> = vec![];
+
+            {
+                let mut nft = self
+                    .nft_vaults
+                    .get_mut(&nft_id)
+                    .expect("[cancel] NFT not found");
+
+                nft_bucket.push(nft.take_all());
+            }
+            {
+                let listing = self
+                    .listings
+                    .get(&nft_id)
+                    .expect("[change_price] Listing not found");
+
+                let emitter_proof = self
+                    .emitter_badge
+                    .as_non_fungible()
+                    .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+                self.event_manager.cancel_listing_event(
+                    listing.clone(),
+                    nft_id.clone(),
+                    emitter_proof.into(),
+                );
+            }
+
+            self.unindex_listing(&nft_id);
+
+            nft_bucket
+        }
+
+        /// Creates proofs of NFTs held in escrow so the user can keep using them (token-gated dApps, bots, games, etc.)
+        /// while they are listed. The NFTs never leave their vaults.
+        pub fn create_listed_nft_proofs(&self, nft_ids: Vec<NonFungibleGlobalId>) -> Vec<Proof> {
+            nft_ids
+                .into_iter()
+                .map(|nft_id| {
+                    let vault = self
+                        .nft_vaults
+                        .get(&nft_id)
+                        .expect("[create_listed_nft_proofs] NFT not found");
+
+                    let (_, local_id) = nft_id.into_parts();
+
+                    assert!(
+                        vault
+                            .as_non_fungible()
+                            .non_fungible_local_ids(1)
+                            .contains(&local_id),
+                        "[create_listed_nft_proofs] NFT is no longer held by this account"
+                    );
+
+                    vault
+                        .as_non_fungible()
+                        .create_proof_of_non_fungibles(&indexset![local_id])
+                        .into()
+                })
+                .collect()
+        }
+
+        /// Returns the NFT, the marketplace fee and any change from a payment above the listing price.
+        pub fn purchase_listing(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            permission: Proof,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            self.purchase_listing_at(nfgid, payment, permission, None)
+        }
+
+        /// The standard NFT version of purchase_royal_listing_with_fee_rate.
+        pub fn purchase_listing_with_fee_rate(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            payment: FungibleBucket,
+            permission: Proof,
+            marketplace_fee_rate: Decimal,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            self.purchase_listing_at(nfgid, payment, permission, Some(marketplace_fee_rate))
+        }
+
+        fn purchase_listing_at(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            mut payment: FungibleBucket,
+            permission: Proof,
+            scheduled_fee_rate: Option<Decimal>,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            let (_nft_resource, nft_local) = nfgid.clone().into_parts();
+
+            let mut return_buckets: (Vec<Bucket>, Vec<Bucket>) = (vec![], vec![]);
+            let listing_event: Listing;
+
+            let change = {
+                let listing_permission = self
+                    .listings
+                    .get(&nfgid)
                     .expect("[purchase] Listing not found");
 
                 assert!(
-                    listing_permission
-                        .secondary_seller_permissions
-                        .contains(&marketplace),
+                    listing_permission.permits(&permission, self.auth_key_resource),
                     "[purchase] Marketplace does not have permission to purchase this listing"
                 );
-            }
-            // We get the marketplace fee rate from the metadata of the proof
+
+                assert!(
+                    !listing_permission.is_expired(),
+                    "[purchase] Listing has expired"
+                );
+
+                Self::take_change(&listing_permission, &mut payment)
+            };
+
+            // We get the marketplace fee rate from the metadata of the proof, unless the marketplace has scheduled a lower rate
             // We calculate the marketplace fee from the payment amount.
             // This could be an unsafe decimal at this point - however when taking from the payment we use a safe rounding mode.
             // If not marketplace fee is set, we set the rate to 0.
 
-            let marketplace_fee_option: Option<Decimal> = permission
-                .skip_checking()
-                .resource_manager()
-                .get_metadata("marketplace_fee")
-                .unwrap();
+            let listing_max_marketplace_fee = self
+                .listings
+                .get(&nfgid)
+                .expect("[purchase] Listing not found")
+                .max_marketplace_fee;
+
+            let marketplace_fee_option: Option<Decimal> =
+                Self::marketplace_fee_rate(permission.resource_address(), scheduled_fee_rate);
+
+            if let Some(marketplace_fee_rate) = marketplace_fee_option {
+                self.assert_marketplace_fee_allowed(
+                    marketplace_fee_rate,
+                    listing_max_marketplace_fee,
+                );
+            }
 
             let marketplace_fee_rate: Decimal;
             let marketplace_fee: Decimal;
@@ -798,20 +1651,13 @@ mod opentrader {
 
                 listing_event = listing.clone();
 
-                let price = listing.price;
+                let price = listing.current_price_in(payment.resource_address());
 
                 assert!(
                     payment.amount() == price,
                     "[purchase] Payment amount does not match listing price"
                 );
 
-                let currency = listing.currency;
-
-                assert!(
-                    payment.resource_address() == currency,
-                    "[purchase] Payment currency does not match listing currency",
-                );
-
                 {
                     let mut nft = self
                         .nft_vaults
@@ -852,15 +1698,1831 @@ mod opentrader {
                 emitter_proof.into(),
             );
 
-            self.listings.remove(&nfgid);
+            self.remove_sold_listing(&nfgid);
 
-            return_buckets
+            (
+                return_buckets.0,
+                return_buckets.1,
+                change.map(|change| change.into()),
+            )
         }
 
-        // utility methods
+        //
+        // Batch Methods //
+        //
 
-        pub fn fetch_auth_key(&self) -> (ResourceAddress, NonFungibleLocalId) {
-            (self.auth_key_resource, self.auth_key_local.clone())
+        /// Lists many NFTs (royalty enforced or standard) at once, each with its own terms. Every NFT in the buckets must have terms.
+        /// A single batched event is emitted for all the listings to keep fees low.
+        pub fn batch_list(
+            &mut self,
+            nfts: Vec<Bucket>,
+            terms: HashMap<NonFungibleGlobalId, ListingTerms>,
+        ) {
+            let mut new_listings: Vec<Listing> = vec![];
+            let mut contains_royalty_nft = false;
+
+            for nft_bucket in nfts {
+                let nft_address = nft_bucket.resource_address();
+
+                if self.royalty_component_of(nft_address).is_some() {
+                    contains_royalty_nft = true;
+                }
+
+                for nft_local in nft_bucket.as_non_fungible().non_fungible_local_ids() {
+                    let nfgid = NonFungibleGlobalId::new(nft_address, nft_local.clone());
+
+                    let listing_terms = terms
+                        .get(&nfgid)
+                        .expect("[batch_list] Listing terms not provided for every NFT")
+                        .clone();
+
+                    assert!(
+                        listing_terms.price > Decimal::zero(),
+                        "[batch_list] Listing price must be greater than zero"
+                    );
+
+                    for (currency, price) in listing_terms.alternative_prices.iter() {
+                        assert!(
+                            *currency != listing_terms.currency && *price > Decimal::zero(),
+                            "[batch_list] Alternative prices must be greater than zero and in another currency"
+                        );
+                    }
+
+                    Self::assert_max_marketplace_fee_in_range(
+                        listing_terms.max_marketplace_fee,
+                        "batch_list",
+                    );
+
+                    let nft: Bucket = nft_bucket
+                        .as_non_fungible()
+                        .take_non_fungible(&nft_local)
+                        .into();
+
+                    // Royalty NFTs need the royalty admin badge to be deposited - this is harmless for standard NFTs.
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        let vault_exists = self.nft_vaults.get(&nfgid).is_some();
+
+                        if vault_exists {
+                            self.nft_vaults
+                                .get_mut(&nfgid)
+                                .expect("[batch_list] NFT not found")
+                                .put(nft);
+                        } else {
+                            self.nft_vaults
+                                .insert(nfgid.clone(), Vault::with_bucket(nft));
+                        }
+                    });
+
+                    let new_listing = Listing {
+                        secondary_seller_permissions: listing_terms.permissions,
+                        currency: listing_terms.currency,
+                        price: listing_terms.price,
+                        nfgid: nfgid.clone(),
+                        open_trader_account: self.trader_account_component_address,
+                        dutch_auction: None,
+                        alternative_prices: listing_terms.alternative_prices,
+                        expiry: None,
+                        private_buyers: vec![],
+                        max_marketplace_fee: listing_terms.max_marketplace_fee,
+                    };
+
+                    self.index_listing(nfgid, new_listing.clone());
+
+                    new_listings.push(new_listing);
+                }
+
+                nft_bucket.drop_empty();
+            }
+
+            assert!(!new_listings.is_empty(), "[batch_list] No NFTs provided");
+
+            assert!(
+                new_listings.len() == terms.len(),
+                "[batch_list] Listing terms provided for NFTs that aren't being listed"
+            );
+
+            let listed_nfts: Vec<NonFungibleGlobalId> = new_listings
+                .iter()
+                .map(|listing| listing.nfgid.clone())
+                .collect();
+
+            self.update_listing_states(&listed_nfts, true);
+
+            // As with royal listings, we block royalty NFTs being listed and purchased in the same transaction.
+            if contains_royalty_nft {
+                let transaction_hash = Runtime::transaction_hash();
+
+                self.transactions.insert(transaction_hash, ());
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager.batch_listing_event(
+                ListingBatchAction::Created,
+                new_listings,
+                emitter_proof.into(),
+            );
+        }
+
+        /// Changes the prices of many listings at once - each change is the listing, the currency and the new price.
+        /// A change in the listing's own currency updates its price, while any other currency sets an alternative price.
+        pub fn batch_change_price(
+            &mut self,
+            changes: Vec<(NonFungibleGlobalId, ResourceAddress, Decimal)>,
+        ) {
+            assert!(
+                !changes.is_empty(),
+                "[batch_change_price] No price changes provided"
+            );
+
+            let mut updated_listings: Vec<Listing> = vec![];
+
+            for (nft_id, currency, new_price) in changes {
+                let mut listing = self
+                    .listings
+                    .get_mut(&nft_id)
+                    .expect("[batch_change_price] Listing not found");
+
+                if currency == listing.currency {
+                    listing.set_price(new_price);
+                } else {
+                    listing.set_alternative_price(currency, new_price);
+                }
+
+                updated_listings.push(listing.clone());
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager.batch_listing_event(
+                ListingBatchAction::Updated,
+                updated_listings,
+                emitter_proof.into(),
+            );
+        }
+
+        /// Cancels many listings at once. Royalty NFTs are deposited back to the user's account and standard NFTs are returned.
+        pub fn batch_cancel_listings(&mut self, nft_ids: Vec<NonFungibleGlobalId>) -> Vec<Bucket> {
+            assert!(
+                !nft_ids.is_empty(),
+                "[batch_cancel_listings] No listings provided"
+            );
+
+            let mut nft_buckets: Vec<Bucket> = vec![];
+            let mut canceled_listings: Vec<Listing> = vec![];
+
+            for nft_id in nft_ids {
+                let (nft_address, nft_local) = nft_id.clone().into_parts();
+
+                let listing = self
+                    .unindex_listing(&nft_id)
+                    .expect("[batch_cancel_listings] Listing not found");
+
+                let nft: Bucket = self
+                    .nft_vaults
+                    .get_mut(&nft_id)
+                    .expect("[batch_cancel_listings] NFT not found")
+                    .as_non_fungible()
+                    .take_non_fungible(&nft_local)
+                    .into();
+
+                if self.royalty_component_of(nft_address).is_some() {
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        self.my_account.try_deposit_or_abort(nft, None);
+                    });
+                } else {
+                    nft_buckets.push(nft);
+                }
+
+                canceled_listings.push(listing);
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager.batch_listing_event(
+                ListingBatchAction::Canceled,
+                canceled_listings,
+                emitter_proof.into(),
+            );
+
+            nft_buckets
+        }
+
+        //
+        // Offer Methods //
+        //
+
+        /// Makes an offer on one or more NFTs and/or collections, escrowing the payment in this trader account.
+        /// The same escrowed tokens back every target of the offer - so a user can bid on several NFTs at once,
+        /// but only the first target accepted is filled. Each price must be covered by the escrowed payment.
+        pub fn make_offer(
+            &mut self,
+            payment: FungibleBucket,
+            targets: Vec<(OfferTarget, Decimal)>,
+        ) -> u64 {
+            assert!(
+                !targets.is_empty(),
+                "[make_offer] No offer targets provided"
+            );
+
+            for (_, price) in targets.iter() {
+                assert!(
+                    *price > Decimal::zero(),
+                    "[make_offer] Offer price must be greater than zero"
+                );
+                assert!(
+                    *price <= payment.amount(),
+                    "[make_offer] Offer price is greater than the escrowed payment"
+                );
+            }
+
+            // As with royal listings, we record the transaction hash so an offer can't be made and accepted
+            // atomically to trade a Royalty NFT for a token amount without the risk of being sniped.
+            let transaction_hash = Runtime::transaction_hash();
+
+            self.transactions.insert(transaction_hash, ());
+
+            let offer_id = self.offer_id_counter;
+            self.offer_id_counter += 1;
+
+            let currency = payment.resource_address();
+
+            let new_offer = Offer {
+                offer_id,
+                targets,
+                currency,
+                escrowed_amount: payment.amount(),
+                open_trader_account: self.trader_account_component_address,
+                expiry: None,
+            };
+
+            let vault_exists = self.offer_escrow.get(&currency).is_some();
+
+            if vault_exists {
+                self.offer_escrow
+                    .get_mut(&currency)
+                    .unwrap()
+                    .put(payment.into());
+            } else {
+                self.offer_escrow
+                    .insert(currency, Vault::with_bucket(payment.into()));
+            }
+
+            self.offers.insert(offer_id, new_offer.clone());
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .offer_event(new_offer, emitter_proof.into());
+
+            offer_id
+        }
+
+        /// Sets or removes the time an offer expires. An expired offer can't be accepted, but its escrow is only returned
+        /// when the offer is cancelled.
+        pub fn set_offer_expiry(&mut self, offer_id: u64, expiry: Option<Instant>) {
+            let mut offer = self
+                .offers
+                .get_mut(&offer_id)
+                .expect("[set_offer_expiry] Offer not found");
+
+            offer.expiry = expiry;
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .update_offer_event(offer.clone(), emitter_proof.into());
+        }
+
+        /// Cancels an offer and returns the escrowed tokens backing it.
+        pub fn cancel_offer(&mut self, offer_id: u64) -> Bucket {
+            let offer = self
+                .offers
+                .remove(&offer_id)
+                .expect("[cancel_offer] Offer not found");
+
+            let escrow = self
+                .offer_escrow
+                .get_mut(&offer.currency)
+                .unwrap()
+                .take(offer.escrowed_amount);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .cancel_offer_event(offer, emitter_proof.into());
+
+            escrow
+        }
+
+        /// Accepts an offer made from this trader account by providing an NFT that matches one of its targets.
+        /// The NFT is sent to the offer maker's account and the seller receives the offered price back, less any royalty.
+        /// Royalty NFTs are paid for through the collection's royalty component exactly like a royal listing purchase.
+        /// This is the offer maker's trader account, so the buyer provided to the royalty component is the offer maker's
+        /// trader key resource - creators who limit who can buy their NFTs can permit it like a marketplace badge.
+        /// An offer is filled once - accepting one target closes the offer for all of its other targets and any escrow
+        /// not spent on the accepted target is returned to the offer maker.
+        pub fn accept_offer(&mut self, offer_id: u64, nft: Bucket) -> Vec<Bucket> {
+            assert!(
+                nft.amount() == dec!(1),
+                "[accept_offer] Only one NFT can be sold at a time"
+            );
+
+            let nft_address = nft.resource_address();
+
+            let nfgid = NonFungibleGlobalId::new(
+                nft_address,
+                nft.as_non_fungible().non_fungible_local_id(),
+            );
+
+            let offer = self
+                .offers
+                .remove(&offer_id)
+                .expect("[accept_offer] Offer not found");
+
+            assert!(!offer.is_expired(), "[accept_offer] Offer has expired");
+
+            let price = offer
+                .targets
+                .iter()
+                .find(|(target, _)| match target {
+                    OfferTarget::Nft(target_nfgid) => target_nfgid == &nfgid,
+                    OfferTarget::Collection(collection) => collection == &nft_address,
+                })
+                .map(|(_, price)| *price)
+                .expect("[accept_offer] This NFT does not match the offer");
+
+            let (payment, unspent_escrow) = {
+                let mut escrow = self.offer_escrow.get_mut(&offer.currency).unwrap();
+
+                (
+                    escrow.take(price),
+                    escrow.take(offer.escrowed_amount.checked_sub(price).unwrap()),
+                )
+            };
+
+            let locker_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            // The unspent escrow is returned to the offer maker as the offer can only be filled once.
+            if !unspent_escrow.is_empty() {
+                locker_proof.authorize(|| {
+                    self.account_locker
+                        .store(self.my_account, unspent_escrow, true);
+                });
+            } else {
+                unspent_escrow.drop_empty();
+            }
+
+            let mut proceeds: Vec<Bucket> = vec![];
+
+            // The offer maker is the buyer - not the seller accepting the offer.
+            let offer_maker_key = self.auth_key_resource;
+
+            if let Some(royalty_component) = self.royalty_component_of(nft_address) {
+                // An offer can't be made and accepted for a Royalty NFT in the same transaction.
+                let transaction_hash = Runtime::transaction_hash();
+
+                assert!(
+                    self.transactions.get(&transaction_hash).is_none(),
+                    "[accept_offer] Accepting an offer within the same transaction it is made is blocked."
+                );
+
+                let remainder_after_royalty: Bucket = Self::pay_royalty(
+                    royalty_component,
+                    nfgid.clone(),
+                    price,
+                    payment,
+                    offer_maker_key,
+                );
+
+                proceeds.push(remainder_after_royalty);
+            } else {
+                proceeds.push(payment);
+            }
+
+            self.deliver_nft(nft, self.my_account);
+
+            self.event_manager
+                .accept_offer_event(offer, nfgid, price, locker_proof.into());
+
+            proceeds
+        }
+
+        /// Accepts another trader's offer using an NFT that is currently listed in this trader account.
+        /// The listing is cancelled and the proceeds of the sale are sent to the user's account.
+        /// Only fixed price and dutch listings can be used - an auctioned NFT may already have bids and a bundled NFT
+        /// is sold as part of its lot, so auctions and bundles have to be cancelled before their NFTs can fill an offer.
+        pub fn accept_offer_with_listing(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            offer_trader_account: Global<AnyComponent>,
+            offer_id: u64,
+        ) {
+            let (_nft_resource, nft_local) = nfgid.clone().into_parts();
+
+            let listing = self
+                .remove_sold_listing(&nfgid)
+                .expect("[accept_offer_with_listing] Listing not found - auctions and bundles must be cancelled first");
+
+            let nft: Bucket = self
+                .nft_vaults
+                .get_mut(&nfgid)
+                .expect("[accept_offer_with_listing] NFT not found")
+                .as_non_fungible()
+                .take_non_fungible(&nft_local)
+                .into();
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .cancel_listing_event(listing, nfgid, emitter_proof.clone().into());
+
+            let proceeds: Vec<Bucket> = offer_trader_account
+                .call_raw::<Vec<Bucket>>("accept_offer", scrypto_args!(offer_id, nft));
+
+            emitter_proof.authorize(|| {
+                for revenue in proceeds {
+                    self.account_locker.store(self.my_account, revenue, true);
+                }
+            });
+        }
+
+        //
+        // Auction Methods //
+        //
+
+        /// Lists an NFT (royalty enforced or standard) in a timed ascending auction. Bids must meet the reserve price and then
+        /// beat the current highest bid by the minimum increment. A bid placed within the extension window of the end time
+        /// pushes the end time back so the auction can't be sniped in the final seconds.
+        pub fn auction_list(
+            &mut self,
+            nft_to_list: Bucket,
+            currency: ResourceAddress,
+            reserve_price: Decimal,
+            minimum_increment: Decimal,
+            timing: AuctionTiming,
+            permissions: Vec<ResourceAddress>,
+        ) {
+            let AuctionTiming {
+                start_time,
+                end_time,
+                extension_seconds,
+            } = timing;
+
+            assert!(
+                nft_to_list.amount() == dec!(1),
+                "[auction_list] Only one NFT can be auctioned at a time"
+            );
+
+            assert!(
+                reserve_price > Decimal::zero(),
+                "[auction_list] Reserve price must be greater than zero"
+            );
+
+            assert!(
+                minimum_increment > Decimal::zero(),
+                "[auction_list] Minimum increment must be greater than zero"
+            );
+
+            assert!(
+                extension_seconds >= 0,
+                "[auction_list] Extension window can not be negative"
+            );
+
+            assert!(
+                start_time.compare(end_time, TimeComparisonOperator::Lt),
+                "[auction_list] Auction must start before it ends"
+            );
+
+            assert!(
+                Clock::current_time_is_strictly_before(end_time, TimePrecision::Second),
+                "[auction_list] Auction end time must be in the future"
+            );
+
+            let nfgid = NonFungibleGlobalId::new(
+                nft_to_list.resource_address(),
+                nft_to_list.as_non_fungible().non_fungible_local_id(),
+            );
+
+            let new_auction = Auction {
+                secondary_seller_permissions: permissions,
+                currency,
+                reserve_price,
+                minimum_increment,
+                start_time,
+                end_time,
+                extension_seconds,
+                highest_bid: Decimal::zero(),
+                highest_bidder: None,
+                highest_bid_permission: None,
+                nfgid: nfgid.clone(),
+                open_trader_account: self.trader_account_component_address,
+            };
+
+            self.auctions.insert(nfgid.clone(), new_auction.clone());
+
+            // Royalty NFTs need the royalty admin badge to be deposited - this is harmless for standard NFTs.
+            self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                let vault_exists = self.nft_vaults.get(&nfgid).is_some();
+
+                if vault_exists {
+                    self.nft_vaults
+                        .get_mut(&nfgid)
+                        .expect("[auction_list] NFT not found")
+                        .put(nft_to_list);
+                } else {
+                    self.nft_vaults
+                        .insert(nfgid.clone(), Vault::with_bucket(nft_to_list));
+                }
+            });
+
+            self.update_listing_states(std::slice::from_ref(&nfgid), true);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .auction_event(new_auction, emitter_proof.into());
+        }
+
+        /// Places a bid on an auction. Like listings, a bid must be placed with the badge of a marketplace or private buyer that
+        /// the seller has permitted. The bid is escrowed in this trader account and the previous highest bidder is refunded
+        /// to their account via the AccountLocker. The bidder must be the owner of the account the bid is placed for - the owner's
+        /// proof is checked against this call's auth zone, so bids have to be placed on the trader account directly rather than
+        /// routed through a marketplace component, which would hide the bidder's signature.
+        pub fn place_bid(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            bid: FungibleBucket,
+            permission: Proof,
+            bidder: Global<Account>,
+        ) {
+            Runtime::assert_access_rule(bidder.get_owner_role().rule);
+
+            let trading_permission = permission.resource_address();
+
+            let mut auction = self
+                .auctions
+                .get(&nfgid)
+                .expect("[place_bid] Auction not found")
+                .clone();
+
+            assert!(
+                auction
+                    .secondary_seller_permissions
+                    .contains(&trading_permission),
+                "[place_bid] Marketplace does not have permission to bid on this auction"
+            );
+
+            assert!(
+                bid.resource_address() == auction.currency,
+                "[place_bid] Bid currency does not match auction currency"
+            );
+
+            assert!(
+                Clock::current_time_is_at_or_after(auction.start_time, TimePrecision::Second),
+                "[place_bid] Auction has not started"
+            );
+
+            assert!(
+                Clock::current_time_is_strictly_before(auction.end_time, TimePrecision::Second),
+                "[place_bid] Auction has ended"
+            );
+
+            let minimum_bid = if auction.highest_bidder.is_some() {
+                auction
+                    .highest_bid
+                    .checked_add(auction.minimum_increment)
+                    .unwrap()
+            } else {
+                auction.reserve_price
+            };
+
+            assert!(
+                bid.amount() >= minimum_bid,
+                "[place_bid] Bid is below the minimum required"
+            );
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            // The outbid party is refunded their escrowed bid
+            if let Some(outbid) = auction.highest_bidder {
+                let refund = self
+                    .auction_escrow
+                    .get_mut(&auction.currency)
+                    .unwrap()
+                    .take(auction.highest_bid);
+
+                emitter_proof.authorize(|| {
+                    self.account_locker.store(outbid, refund, true);
+                });
+            }
+
+            auction.highest_bid = bid.amount();
+            auction.highest_bidder = Some(bidder);
+            auction.highest_bid_permission = Some(trading_permission);
+
+            let vault_exists = self.auction_escrow.get(&auction.currency).is_some();
+
+            if vault_exists {
+                self.auction_escrow
+                    .get_mut(&auction.currency)
+                    .unwrap()
+                    .put(bid.into());
+            } else {
+                self.auction_escrow
+                    .insert(auction.currency, Vault::with_bucket(bid.into()));
+            }
+
+            // Anti-sniping - a bid within the extension window resets the end time to the window length from now.
+            let now = Clock::current_time_rounded_to_seconds();
+
+            let extension_start = auction
+                .end_time
+                .add_seconds(-auction.extension_seconds)
+                .unwrap();
+
+            let extended = auction.extension_seconds > 0
+                && now.compare(extension_start, TimeComparisonOperator::Gte);
+
+            if extended {
+                auction.end_time = now.add_seconds(auction.extension_seconds).unwrap();
+            }
+
+            self.auctions.insert(nfgid, auction.clone());
+
+            self.event_manager
+                .auction_bid_event(auction.clone(), emitter_proof.clone().into());
+
+            if extended {
+                self.event_manager
+                    .auction_extended_event(auction, emitter_proof.into());
+            }
+        }
+
+        /// Settles an auction once it has ended. Anyone can settle an auction so the winner doesn't depend on the seller.
+        /// The winning bid is sent to the seller - Royalty NFTs pay their royalty through the collection's royalty component,
+        /// with the winning bid's permission provided as the buyer. A standard NFT is sent to the highest bidder via the
+        /// AccountLocker, while a Royalty NFT is held for the winner to claim with claim_auction_nft - so a winner's account
+        /// that refuses deposits can't stop the auction from settling. If no bids were placed, the NFT is returned to the seller.
+        pub fn settle_auction(&mut self, nfgid: NonFungibleGlobalId) {
+            let (nft_address, nft_local) = nfgid.clone().into_parts();
+
+            let auction = self
+                .auctions
+                .remove(&nfgid)
+                .expect("[settle_auction] Auction not found");
+
+            assert!(
+                Clock::current_time_is_at_or_after(auction.end_time, TimePrecision::Second),
+                "[settle_auction] Auction has not ended"
+            );
+
+            let royalty_component = self.royalty_component_of(nft_address);
+
+            if let Some(winner) = auction.highest_bidder {
+                self.update_listing_states(std::slice::from_ref(&nfgid), false);
+
+                let payment = self
+                    .auction_escrow
+                    .get_mut(&auction.currency)
+                    .unwrap()
+                    .take(auction.highest_bid);
+
+                let revenue = match royalty_component {
+                    Some(royalty_component) => Self::pay_royalty(
+                        royalty_component,
+                        nfgid.clone(),
+                        auction.highest_bid,
+                        payment,
+                        auction.highest_bid_permission.unwrap(),
+                    ),
+                    None => payment,
+                };
+
+                let locker_proof = self
+                    .emitter_badge
+                    .as_non_fungible()
+                    .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+                locker_proof.authorize(|| {
+                    self.account_locker.store(self.my_account, revenue, true);
+                });
+
+                if royalty_component.is_some() {
+                    self.unclaimed_auction_nfts.insert(nfgid.clone(), winner);
+                } else {
+                    let nft = self.take_nft(&nfgid, &nft_local);
+                    self.deliver_nft(nft, winner);
+                }
+            } else {
+                let nft = self.take_nft(&nfgid, &nft_local);
+                self.deliver_nft(nft, self.my_account);
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .auction_settled_event(auction, emitter_proof.into());
+        }
+
+        /// Claims a Royalty NFT won at auction. Only the owner of the winning account can claim it, and it can only be
+        /// deposited to that account.
+        pub fn claim_auction_nft(
+            &mut self,
+            nfgid: NonFungibleGlobalId,
+            mut account: Global<Account>,
+        ) {
+            let winner = self
+                .unclaimed_auction_nfts
+                .remove(&nfgid)
+                .expect("[claim_auction_nft] No unclaimed auction NFT found");
+
+            assert!(
+                winner.address() == account.address(),
+                "[claim_auction_nft] Only the winner of the auction can claim this NFT"
+            );
+
+            Runtime::assert_access_rule(account.get_owner_role().rule);
+
+            let (_nft_resource, nft_local) = nfgid.clone().into_parts();
+
+            let nft = self.take_nft(&nfgid, &nft_local);
+
+            self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                account.try_deposit_or_abort(nft, None);
+            });
+        }
+
+        /// Cancels an auction that hasn't received any bids, returning the NFT to the user's account.
+        pub fn cancel_auction(&mut self, nfgid: NonFungibleGlobalId) {
+            let (_nft_resource, nft_local) = nfgid.clone().into_parts();
+
+            let auction = self
+                .auctions
+                .remove(&nfgid)
+                .expect("[cancel_auction] Auction not found");
+
+            assert!(
+                auction.highest_bidder.is_none(),
+                "[cancel_auction] An auction can not be cancelled once a bid has been placed"
+            );
+
+            let nft: Bucket = self
+                .nft_vaults
+                .get_mut(&nfgid)
+                .expect("[cancel_auction] NFT not found")
+                .as_non_fungible()
+                .take_non_fungible(&nft_local)
+                .into();
+
+            self.deliver_nft(nft, self.my_account);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .auction_canceled_event(auction, emitter_proof.into());
+        }
+
+        //
+        // Bundle Methods //
+        //
+
+        /// Lists several NFTs (royalty enforced or standard, from any number of collections) to be sold together for one price.
+        /// The seller can declare the portion of the price each NFT is sold for - otherwise the price is split equally.
+        /// When the bundle is purchased each royalty enforced NFT pays its royalty on its own portion of the price.
+        pub fn bundle_list(
+            &mut self,
+            nfts: Vec<Bucket>,
+            currency: ResourceAddress,
+            price: Decimal,
+            price_split: Option<HashMap<NonFungibleGlobalId, Decimal>>,
+            permissions: Vec<ResourceAddress>,
+            max_marketplace_fee: Option<Decimal>,
+        ) -> u64 {
+            let nfgids: Vec<NonFungibleGlobalId> = nfts
+                .iter()
+                .flat_map(|nft_bucket| {
+                    let nft_address = nft_bucket.resource_address();
+
+                    nft_bucket
+                        .as_non_fungible()
+                        .non_fungible_local_ids()
+                        .into_iter()
+                        .map(move |nft_local| NonFungibleGlobalId::new(nft_address, nft_local))
+                })
+                .collect();
+
+            assert!(
+                nfgids.len() > 1,
+                "[bundle_list] A bundle must contain at least two NFTs"
+            );
+
+            assert!(
+                price > Decimal::zero(),
+                "[bundle_list] Price must be greater than zero"
+            );
+
+            Self::assert_max_marketplace_fee_in_range(max_marketplace_fee, "bundle_list");
+
+            let items: Vec<(NonFungibleGlobalId, Decimal)> = match price_split {
+                Some(price_split) => {
+                    assert!(
+                        price_split.len() == nfgids.len(),
+                        "[bundle_list] The price split must cover every NFT in the bundle"
+                    );
+
+                    let items: Vec<(NonFungibleGlobalId, Decimal)> = nfgids
+                        .into_iter()
+                        .map(|nfgid| {
+                            let portion = *price_split.get(&nfgid).expect(
+                                "[bundle_list] The price split must cover every NFT in the bundle",
+                            );
+
+                            assert!(
+                                portion >= Decimal::zero(),
+                                "[bundle_list] A portion of the price can not be negative"
+                            );
+
+                            // Otherwise the whole price could be put on standard NFTs to skip the royalty.
+                            assert!(
+                                portion > Decimal::zero()
+                                    || self.royalty_component_of(nfgid.resource_address()).is_none(),
+                                "[bundle_list] A royalty NFT's portion of the price must be greater than zero"
+                            );
+
+                            (nfgid, portion)
+                        })
+                        .collect();
+
+                    let split_total = items.iter().fold(Decimal::zero(), |total, (_, portion)| {
+                        total.checked_add(*portion).unwrap()
+                    });
+
+                    assert!(
+                        split_total == price,
+                        "[bundle_list] The price split must add up to the bundle price"
+                    );
+
+                    items
+                }
+                None => {
+                    let nft_count = nfgids.len();
+                    let portion = price.checked_div(Decimal::from(nft_count)).unwrap();
+                    // Any dust left from dividing the price is added to the last NFT's portion
+                    let last_portion = price
+                        .checked_sub(portion.checked_mul(Decimal::from(nft_count - 1)).unwrap())
+                        .unwrap();
+
+                    nfgids
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, nfgid)| {
+                            if index == nft_count - 1 {
+                                (nfgid, last_portion)
+                            } else {
+                                (nfgid, portion)
+                            }
+                        })
+                        .collect()
+                }
+            };
+
+            // As with royal listings, we block a bundle being listed and purchased in the same transaction.
+            let transaction_hash = Runtime::transaction_hash();
+
+            self.transactions.insert(transaction_hash, ());
+
+            // Royalty NFTs need the royalty admin badge to be deposited - this is harmless for standard NFTs.
+            self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                for nft_bucket in nfts {
+                    let nft_address = nft_bucket.resource_address();
+
+                    for nft_local in nft_bucket.as_non_fungible().non_fungible_local_ids() {
+                        let nfgid = NonFungibleGlobalId::new(nft_address, nft_local.clone());
+                        let nft: Bucket = nft_bucket
+                            .as_non_fungible()
+                            .take_non_fungible(&nft_local)
+                            .into();
+
+                        let vault_exists = self.nft_vaults.get(&nfgid).is_some();
+
+                        if vault_exists {
+                            self.nft_vaults
+                                .get_mut(&nfgid)
+                                .expect("[bundle_list] NFT not found")
+                                .put(nft);
+                        } else {
+                            self.nft_vaults.insert(nfgid, Vault::with_bucket(nft));
+                        }
+                    }
+
+                    nft_bucket.drop_empty();
+                }
+            });
+
+            let bundle_id = self.bundle_id_counter;
+            self.bundle_id_counter += 1;
+
+            let bundle = Bundle {
+                bundle_id,
+                secondary_seller_permissions: permissions,
+                currency,
+                price,
+                items,
+                open_trader_account: self.trader_account_component_address,
+                max_marketplace_fee,
+                private_buyers: vec![],
+            };
+
+            self.bundles.insert(bundle_id, bundle.clone());
+
+            self.update_listing_states(&bundle.nfgids(), true);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .bundle_event(bundle, emitter_proof.into());
+
+            bundle_id
+        }
+
+        /// Purchases a bundle with the badge of a marketplace or private buyer the seller has permitted.
+        /// Royalty NFTs are deposited straight to the account recipient, while standard NFTs are returned to the caller.
+        /// Returns the marketplace fee, the standard NFTs and any change from a payment above the bundle price.
+        pub fn purchase_bundle(
+            &mut self,
+            bundle_id: u64,
+            mut payment: FungibleBucket,
+            permission: Proof,
+            mut account_recipient: Global<Account>,
+        ) -> (Vec<Bucket>, Vec<Bucket>, Option<Bucket>) {
+            // fee payment is tuple 1, standard NFTs are tuple 2
+            let mut tuple_buckets: (Vec<Bucket>, Vec<Bucket>) = (vec![], vec![]);
+
+            let trading_permission = permission.resource_address();
+
+            let bundle = self
+                .bundles
+                .remove(&bundle_id)
+                .expect("[purchase_bundle] Bundle not found");
+
+            self.update_listing_states(&bundle.nfgids(), false);
+
+            assert!(
+                bundle.permits(&permission, self.auth_key_resource),
+                "[purchase_bundle] Marketplace does not have permission to purchase this bundle"
+            );
+
+            assert!(
+                payment.resource_address() == bundle.currency,
+                "[purchase_bundle] Payment currency does not match bundle currency",
+            );
+
+            assert!(
+                payment.amount() >= bundle.price,
+                "[purchase_bundle] Payment amount is below the bundle price"
+            );
+
+            // As with listings, anything paid above the price is returned so fees and royalties are only taken on the price.
+            let change: Option<Bucket> = if payment.amount() > bundle.price {
+                Some(
+                    payment
+                        .take(payment.amount().checked_sub(bundle.price).unwrap())
+                        .into(),
+                )
+            } else {
+                None
+            };
+
+            let transaction_hash = Runtime::transaction_hash();
+
+            assert!(
+                self.transactions.get(&transaction_hash).is_none(),
+                "[purchase_bundle] Purchasing a bundle within the same transaction it is listed is blocked."
+            );
+
+            let marketplace_fee_option: Option<Decimal> = permission
+                .skip_checking()
+                .resource_manager()
+                .get_metadata("marketplace_fee")
+                .unwrap();
+
+            if let Some(marketplace_fee_rate) = marketplace_fee_option {
+                self.assert_marketplace_fee_allowed(
+                    marketplace_fee_rate,
+                    bundle.max_marketplace_fee,
+                );
+            }
+
+            let marketplace_fee = match marketplace_fee_option {
+                Some(marketplace_fee_rate) => {
+                    payment.amount().checked_mul(marketplace_fee_rate).unwrap()
+                }
+                None => dec!(0),
+            };
+
+            // Each Royalty NFT is paid its royalty on its own portion of the price, so creators can set royalties per NFT.
+            let mut payment: Bucket = payment.into();
+
+            for (nfgid, portion) in bundle.items.iter() {
+                if let Some(royalty_component) = self.royalty_component_of(nfgid.resource_address())
+                {
+                    let item_payment = payment.take(*portion);
+
+                    let remainder_after_royalty: Bucket = Self::pay_royalty(
+                        royalty_component,
+                        nfgid.clone(),
+                        *portion,
+                        item_payment,
+                        trading_permission,
+                    );
+
+                    payment.put(remainder_after_royalty);
+                }
+            }
+
+            if marketplace_fee > dec!(0) {
+                let marketplace_revenue = payment.take_advanced(
+                    marketplace_fee,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
+
+                tuple_buckets.0.push(marketplace_revenue);
+            }
+
+            let locker_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            locker_proof.authorize(|| {
+                self.account_locker.store(self.my_account, payment, true);
+            });
+
+            for (nfgid, _portion) in bundle.items.iter() {
+                let (nft_address, nft_local) = nfgid.clone().into_parts();
+
+                let nft: Bucket = self
+                    .nft_vaults
+                    .get_mut(nfgid)
+                    .expect("[purchase_bundle] NFT not found")
+                    .as_non_fungible()
+                    .take_non_fungible(&nft_local)
+                    .into();
+
+                if self.royalty_component_of(nft_address).is_some() {
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        account_recipient.try_deposit_or_abort(nft, None);
+                    });
+                } else {
+                    tuple_buckets.1.push(nft);
+                }
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .purchase_bundle_event(bundle, emitter_proof.into());
+
+            (tuple_buckets.0, tuple_buckets.1, change)
+        }
+
+        /// Permits a specific badge to purchase a bundle, so a private deal can be targeted at a single buyer.
+        pub fn add_bundle_private_buyer(&mut self, bundle_id: u64, buyer: NonFungibleGlobalId) {
+            let mut bundle = self
+                .bundles
+                .get_mut(&bundle_id)
+                .expect("[add_bundle_private_buyer] Bundle not found");
+
+            bundle.private_buyers.push(buyer);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .bundle_event(bundle.clone(), emitter_proof.into());
+        }
+
+        pub fn revoke_bundle_private_buyer(&mut self, bundle_id: u64, buyer: NonFungibleGlobalId) {
+            let mut bundle = self
+                .bundles
+                .get_mut(&bundle_id)
+                .expect("[revoke_bundle_private_buyer] Bundle not found");
+
+            bundle
+                .private_buyers
+                .retain(|private_buyer| private_buyer != &buyer);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .bundle_event(bundle.clone(), emitter_proof.into());
+        }
+
+        /// Cancels a bundle. Standard NFTs are returned to the caller and royalty NFTs are deposited to the user's account.
+        pub fn cancel_bundle(&mut self, bundle_id: u64) -> Vec<Bucket> {
+            let mut nft_buckets: Vec<Bucket> = vec![];
+
+            let bundle = self
+                .bundles
+                .remove(&bundle_id)
+                .expect("[cancel_bundle] Bundle not found");
+
+            for (nfgid, _portion) in bundle.items.iter() {
+                let (nft_address, nft_local) = nfgid.clone().into_parts();
+
+                let nft: Bucket = self
+                    .nft_vaults
+                    .get_mut(nfgid)
+                    .expect("[cancel_bundle] NFT not found")
+                    .as_non_fungible()
+                    .take_non_fungible(&nft_local)
+                    .into();
+
+                if self.royalty_component_of(nft_address).is_some() {
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        self.my_account.try_deposit_or_abort(nft, None);
+                    });
+                } else {
+                    nft_buckets.push(nft);
+                }
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .cancel_bundle_event(bundle, emitter_proof.into());
+
+            nft_buckets
+        }
+
+        //
+        // Swap Methods //
+        //
+
+        /// Proposes a Trove style swap. The user escrows any mix of fungible tokens and NFTs and names the assets they want
+        /// in return, optionally from one specific counterparty. Swaps are free of marketplace fees, but each royalty NFT
+        /// that changes hands pays its collection's swap royalty - the royalty payment covers the NFTs the user is offering.
+        pub fn propose_swap(
+            &mut self,
+            assets: Vec<Bucket>,
+            requested: Vec<SwapAsset>,
+            royalty_payment: Option<FungibleBucket>,
+            counterparty: Option<NonFungibleGlobalId>,
+        ) -> u64 {
+            assert!(
+                !assets.is_empty() && !requested.is_empty(),
+                "[propose_swap] Both sides of a swap must contain assets"
+            );
+
+            let mut offered: Vec<SwapAsset> = vec![];
+
+            for asset in assets {
+                let resource = asset.resource_address();
+
+                if resource.is_fungible() {
+                    offered.push(SwapAsset::Fungible(resource, asset.amount()));
+                } else {
+                    for nft_local in asset.as_non_fungible().non_fungible_local_ids() {
+                        offered.push(SwapAsset::Nft(NonFungibleGlobalId::new(
+                            resource, nft_local,
+                        )));
+                    }
+                }
+
+                self.escrow_swap_asset(asset);
+            }
+
+            let royalty_deposit = royalty_payment.map(|royalty_payment| {
+                let deposit = (royalty_payment.resource_address(), royalty_payment.amount());
+                self.escrow_swap_asset(royalty_payment.into());
+                deposit
+            });
+
+            // As with royal listings, we block a swap being proposed and accepted in the same transaction.
+            let transaction_hash = Runtime::transaction_hash();
+
+            self.transactions.insert(transaction_hash, ());
+
+            let swap_id = self.swap_id_counter;
+            self.swap_id_counter += 1;
+
+            let swap = Swap {
+                swap_id,
+                offered,
+                requested,
+                counterparty,
+                royalty_deposit,
+                open_trader_account: self.trader_account_component_address,
+            };
+
+            self.swaps.insert(swap_id, swap.clone());
+
+            self.update_listing_states(&swap.offered_nfts(), true);
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager.swap_event(swap, emitter_proof.into());
+
+            swap_id
+        }
+
+        /// Cancels a swap. Royalty NFTs are deposited back to the user's account and everything else is returned.
+        pub fn cancel_swap(&mut self, swap_id: u64) -> Vec<Bucket> {
+            let swap = self
+                .swaps
+                .remove(&swap_id)
+                .expect("[cancel_swap] Swap not found");
+
+            let mut returned: Vec<Bucket> = vec![];
+
+            for asset in swap.offered.iter() {
+                let bucket = self.take_swap_asset(asset);
+
+                if self
+                    .royalty_component_of(bucket.resource_address())
+                    .is_some()
+                {
+                    self.deliver_nft(bucket, self.my_account);
+                } else {
+                    returned.push(bucket);
+                }
+            }
+
+            if let Some((currency, amount)) = swap.royalty_deposit {
+                returned.push(self.take_swap_asset(&SwapAsset::Fungible(currency, amount)));
+            }
+
+            let emitter_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            self.event_manager
+                .cancel_swap_event(swap, emitter_proof.into());
+
+            returned
+        }
+
+        /// Accepts a swap by providing exactly the requested assets. The counterparty shows a badge - which must be the targeted
+        /// badge if the swap names a counterparty - that is also provided as the trader to each royalty NFT's royalty component.
+        /// The royalty payment covers the swap royalties of the royalty NFTs the counterparty is giving, with any excess returned.
+        /// Offered royalty NFTs are deposited to the recipient account and all other offered assets are returned.
+        pub fn accept_swap(
+            &mut self,
+            swap_id: u64,
+            assets: Vec<Bucket>,
+            royalty_payment: Option<FungibleBucket>,
+            counterparty_badge: Proof,
+            mut recipient: Global<Account>,
+        ) -> Vec<Bucket> {
+            let swap = self
+                .swaps
+                .remove(&swap_id)
+                .expect("[accept_swap] Swap not found");
+
+            self.update_listing_states(&swap.offered_nfts(), false);
+
+            let transaction_hash = Runtime::transaction_hash();
+
+            assert!(
+                self.transactions.get(&transaction_hash).is_none(),
+                "[accept_swap] Accepting a swap within the same transaction it is proposed is blocked."
+            );
+
+            let counterparty_resource = counterparty_badge.resource_address();
+
+            if let Some(counterparty) = swap.counterparty.clone() {
+                assert!(
+                    counterparty.resource_address() == counterparty_resource
+                        && counterparty_badge
+                            .skip_checking()
+                            .as_non_fungible()
+                            .non_fungible_local_ids()
+                            .contains(counterparty.local_id()),
+                    "[accept_swap] This swap is reserved for another counterparty"
+                );
+
+                assert!(
+                    !is_recovered_trader_key(&counterparty, self.auth_key_resource),
+                    "[accept_swap] The counterparty's trader key has been replaced"
+                );
+            }
+
+            // We pool the provided assets by resource, then take exactly what was requested - anything left over means the
+            // counterparty provided assets that weren't asked for.
+            let mut provided: IndexMap<ResourceAddress, Bucket> = IndexMap::new();
+
+            for asset in assets {
+                match provided.get_mut(&asset.resource_address()) {
+                    Some(pool) => pool.put(asset),
+                    None => {
+                        provided.insert(asset.resource_address(), asset);
+                    }
+                }
+            }
+
+            let mut received: Vec<Bucket> = vec![];
+
+            for asset in swap.requested.iter() {
+                let received_asset: Bucket = match asset {
+                    SwapAsset::Fungible(resource, amount) => provided
+                        .get_mut(resource)
+                        .expect("[accept_swap] Requested asset not provided")
+                        .take(*amount),
+                    SwapAsset::Nft(nfgid) => provided
+                        .get_mut(&nfgid.resource_address())
+                        .expect("[accept_swap] Requested asset not provided")
+                        .as_non_fungible()
+                        .take_non_fungible(nfgid.local_id())
+                        .into(),
+                };
+
+                received.push(received_asset);
+            }
+
+            for (_resource, leftover) in provided {
+                assert!(
+                    leftover.is_empty(),
+                    "[accept_swap] Provided assets do not match the requested assets"
+                );
+
+                leftover.drop_empty();
+            }
+
+            let mut returned: Vec<Bucket> = vec![];
+
+            // The counterparty pays the swap royalties of the NFTs they're giving.
+            let counterparty_royalty_payment: Bucket = match royalty_payment {
+                Some(royalty_payment) => royalty_payment.into(),
+                None => Bucket::new(XRD),
+            };
+
+            let counterparty_royalty_change = self.pay_swap_royalties(
+                &swap.requested,
+                counterparty_royalty_payment,
+                counterparty_resource,
+            );
+
+            if counterparty_royalty_change.is_empty() {
+                counterparty_royalty_change.drop_empty();
+            } else {
+                returned.push(counterparty_royalty_change);
+            }
+
+            // The user pays the swap royalties of the NFTs they're giving from their royalty deposit.
+            let royalty_deposit: Bucket = match swap.royalty_deposit {
+                Some((currency, amount)) => {
+                    self.take_swap_asset(&SwapAsset::Fungible(currency, amount))
+                }
+                None => Bucket::new(XRD),
+            };
+
+            let royalty_deposit_change =
+                self.pay_swap_royalties(&swap.offered, royalty_deposit, self.auth_key_resource);
+
+            let locker_proof = self
+                .emitter_badge
+                .as_non_fungible()
+                .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+            if royalty_deposit_change.is_empty() {
+                royalty_deposit_change.drop_empty();
+            } else {
+                locker_proof.clone().authorize(|| {
+                    self.account_locker
+                        .store(self.my_account, royalty_deposit_change, true);
+                });
+            }
+
+            // The user receives the requested assets
+            for received_asset in received {
+                if received_asset.resource_address().is_fungible() {
+                    locker_proof.clone().authorize(|| {
+                        self.account_locker
+                            .store(self.my_account, received_asset, true);
+                    });
+                } else {
+                    self.deliver_nft(received_asset, self.my_account);
+                }
+            }
+
+            // The counterparty receives the offered assets
+            for asset in swap.offered.iter() {
+                let bucket = self.take_swap_asset(asset);
+
+                if self
+                    .royalty_component_of(bucket.resource_address())
+                    .is_some()
+                {
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        recipient.try_deposit_or_abort(bucket, None);
+                    });
+                } else {
+                    returned.push(bucket);
+                }
+            }
+
+            self.event_manager
+                .accept_swap_event(swap, locker_proof.into());
+
+            returned
+        }
+
+        //
+        // Internal helpers //
+        //
+
+        /// Escrows an asset of a swap in the swap vault for its resource. Royalty NFTs need the royalty admin badge to be
+        /// deposited - this is harmless for other assets.
+        fn escrow_swap_asset(&mut self, asset: Bucket) {
+            let resource = asset.resource_address();
+
+            self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                let vault_exists = self.swap_escrow.get(&resource).is_some();
+
+                if vault_exists {
+                    self.swap_escrow.get_mut(&resource).unwrap().put(asset);
+                } else {
+                    self.swap_escrow.insert(resource, Vault::with_bucket(asset));
+                }
+            });
+        }
+
+        /// Takes an asset of a swap out of escrow.
+        fn take_swap_asset(&mut self, asset: &SwapAsset) -> Bucket {
+            match asset {
+                SwapAsset::Fungible(resource, amount) => self
+                    .swap_escrow
+                    .get_mut(resource)
+                    .expect("[swap] Escrow not found")
+                    .take(*amount),
+                SwapAsset::Nft(nfgid) => self
+                    .swap_escrow
+                    .get_mut(&nfgid.resource_address())
+                    .expect("[swap] Escrow not found")
+                    .as_non_fungible()
+                    .take_non_fungible(nfgid.local_id())
+                    .into(),
+            }
+        }
+
+        /// Pays the swap royalty of each royalty NFT in one side of a swap through its collection's royalty component,
+        /// returning what's left of the payment.
+        fn pay_swap_royalties(
+            &self,
+            assets: &[SwapAsset],
+            mut payment: Bucket,
+            trader: ResourceAddress,
+        ) -> Bucket {
+            for asset in assets {
+                if let SwapAsset::Nft(nfgid) = asset {
+                    let nft_address = nfgid.resource_address();
+
+                    if let Some(royalty_component) = self.royalty_component_of(nft_address) {
+                        payment = royalty_component.call_raw(
+                            "pay_swap_royalty",
+                            scrypto_args!(nfgid.clone(), payment, trader),
+                        );
+                    }
+                }
+            }
+
+            payment
+        }
+
+        /// A buyer can't always know the exact price their purchase will land at - a Dutch auction listing's price falls every second,
+        /// a price may be changed while their transaction is in flight, or a marketplace may withdraw a rounded amount.
+        /// Instead they can pay at least the current price and the difference is taken from the payment to be returned to them,
+        /// so that fees and royalties are only calculated on the listing price.
+        fn take_change(listing: &Listing, payment: &mut FungibleBucket) -> Option<FungibleBucket> {
+            let price = listing.current_price_in(payment.resource_address());
+
+            assert!(
+                payment.amount() >= price,
+                "[purchase] Payment amount is below the current listing price"
+            );
+
+            if payment.amount() == price {
+                return None;
+            }
+
+            Some(payment.take(payment.amount().checked_sub(price).unwrap()))
+        }
+
+        /// The marketplace fee rate of a purchase - the fee published on the permission badge's metadata, or a rate the
+        /// marketplace has scheduled for this purchase. A scheduled rate can only discount the published fee.
+        fn marketplace_fee_rate(
+            permission_resource: ResourceAddress,
+            scheduled_fee_rate: Option<Decimal>,
+        ) -> Option<Decimal> {
+            let published_fee_rate: Option<Decimal> =
+                ResourceManager::from_address(permission_resource)
+                    .get_metadata("marketplace_fee")
+                    .unwrap();
+
+            match scheduled_fee_rate {
+                Some(scheduled_fee_rate) => {
+                    assert!(
+                        scheduled_fee_rate >= Decimal::zero()
+                            && published_fee_rate
+                                .is_some_and(|published_fee_rate| scheduled_fee_rate <= published_fee_rate),
+                        "[purchase] Scheduled marketplace fee exceeds the marketplace's published fee"
+                    );
+
+                    Some(scheduled_fee_rate)
+                }
+                None => published_fee_rate,
+            }
+        }
+
+        /// Rejects a maximum marketplace fee rate outside of 0 to 1. The method name prefixes the error.
+        fn assert_max_marketplace_fee_in_range(max_marketplace_fee: Option<Decimal>, method: &str) {
+            if let Some(max_marketplace_fee) = max_marketplace_fee {
+                assert!(
+                    max_marketplace_fee >= Decimal::zero() && max_marketplace_fee <= Decimal::one(),
+                    "[{}] Maximum marketplace fee must be between 0 and 1",
+                    method
+                );
+            }
+        }
+
+        /// Rejects a purchase if the marketplace fee is above the listing's or the user's maximum marketplace fee.
+        fn assert_marketplace_fee_allowed(
+            &self,
+            marketplace_fee_rate: Decimal,
+            listing_max_marketplace_fee: Option<Decimal>,
+        ) {
+            if let Some(max_marketplace_fee) = listing_max_marketplace_fee {
+                assert!(
+                    marketplace_fee_rate <= max_marketplace_fee,
+                    "[purchase] Marketplace fee exceeds the listing's maximum marketplace fee"
+                );
+            }
+
+            if let Some(max_marketplace_fee) = self.max_marketplace_fee {
+                assert!(
+                    marketplace_fee_rate <= max_marketplace_fee,
+                    "[purchase] Marketplace fee exceeds the seller's maximum marketplace fee"
+                );
+            }
+        }
+
+        /// Pays the royalty on the sale of a Royalty NFT through its royalty component, returning the payment minus the royalty.
+        /// Components with a "royalty_interface_version" of 2 or more are told the NFT sold and its price so they can set
+        /// royalties per NFT. Older royalty components only have the original pay_royalty method, which takes the collection
+        /// address and the payment - so the payment must be the sale price.
+        fn pay_royalty(
+            royalty_component: Global<AnyComponent>,
+            nfgid: NonFungibleGlobalId,
+            price: Decimal,
+            payment: Bucket,
+            buyer: ResourceAddress,
+        ) -> Bucket {
+            let royalty_interface_version: Option<u32> = royalty_component
+                .get_metadata("royalty_interface_version")
+                .unwrap_or(None);
+
+            if royalty_interface_version.is_some_and(|version| version >= 2) {
+                royalty_component.call_raw(
+                    "pay_nft_royalty",
+                    scrypto_args!(nfgid, price, payment, buyer),
+                )
+            } else {
+                royalty_component.call_raw(
+                    "pay_royalty",
+                    scrypto_args!(nfgid.resource_address(), payment, buyer),
+                )
+            }
+        }
+
+        /// Finds the royalty component of an NFT collection from its top-level resource metadata.
+        /// Standard NFTs don't have a royalty component.
+        fn royalty_component_of(
+            &self,
+            nft_address: ResourceAddress,
+        ) -> Option<Global<AnyComponent>> {
+            let royalty_component_option: Option<GlobalAddress> =
+                ResourceManager::from_address(nft_address)
+                    .get_metadata("royalty_component")
+                    .unwrap_or(None);
+
+            royalty_component_option.map(|royalty_component_global_address| {
+                let royalty_component =
+                    ComponentAddress::new_or_panic(royalty_component_global_address.into());
+
+                Global(ObjectStub::new(ObjectStubHandle::Global(
+                    GlobalAddress::from(royalty_component),
+                )))
+            })
+        }
+
+        /// Takes an NFT out of the vault it's held in by this trader account.
+        fn take_nft(
+            &mut self,
+            nfgid: &NonFungibleGlobalId,
+            nft_local: &NonFungibleLocalId,
+        ) -> Bucket {
+            self.nft_vaults
+                .get_mut(nfgid)
+                .expect("[take_nft] NFT not found")
+                .as_non_fungible()
+                .take_non_fungible(nft_local)
+                .into()
+        }
+
+        /// Sends an NFT held by this trader account to an account. Royalty NFTs are deposited using the royal admin badge,
+        /// while standard NFTs are stored via the AccountLocker in case the account doesn't accept direct deposits.
+        fn deliver_nft(&mut self, nft: Bucket, mut recipient: Global<Account>) {
+            if self.royalty_component_of(nft.resource_address()).is_some() {
+                self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                    recipient.try_deposit_or_abort(nft, None);
+                });
+            } else {
+                let locker_proof = self
+                    .emitter_badge
+                    .as_non_fungible()
+                    .create_proof_of_non_fungibles(&indexset![self.emitter_badge_local.clone()]);
+
+                locker_proof.authorize(|| {
+                    self.account_locker.store(recipient, nft, true);
+                });
+            }
+        }
+
+        /// Adds a listing and indexes it, calling the listing hook of the NFT's royalty component.
+        fn insert_listing(&mut self, nfgid: NonFungibleGlobalId, listing: Listing) {
+            self.update_listing_states(std::slice::from_ref(&nfgid), true);
+
+            self.index_listing(nfgid, listing);
+        }
+
+        /// Removes a sold listing and its index entry, calling the listing hook of the NFT's royalty component.
+        /// Cancelled and expired listings use unindex_listing instead, so the hook can never stop an NFT being returned to its owner.
+        fn remove_sold_listing(&mut self, nfgid: &NonFungibleGlobalId) -> Option<Listing> {
+            self.update_listing_states(std::slice::from_ref(nfgid), false);
+
+            self.unindex_listing(nfgid)
+        }
+
+        /// Adds a listing and indexes it. Relisting an NFT that is already listed replaces its listing.
+        fn index_listing(&mut self, nfgid: NonFungibleGlobalId, listing: Listing) {
+            if self.listing_positions.get(&nfgid).is_none() {
+                self.listing_index.insert(self.listing_count, nfgid.clone());
+                self.listing_positions
+                    .insert(nfgid.clone(), self.listing_count);
+                self.listing_count += 1;
+            }
+
+            self.listings.insert(nfgid, listing);
+        }
+
+        /// Removes a listing and its index entry, moving the last listing in the index into its position.
+        fn unindex_listing(&mut self, nfgid: &NonFungibleGlobalId) -> Option<Listing> {
+            if let Some(position) = self.listing_positions.remove(nfgid) {
+                let last_position = self.listing_count - 1;
+
+                if position != last_position {
+                    let last_nfgid = self.listing_index.remove(&last_position).unwrap();
+                    self.listing_positions.insert(last_nfgid.clone(), position);
+                    self.listing_index.insert(position, last_nfgid);
+                } else {
+                    self.listing_index.remove(&last_position);
+                }
+
+                self.listing_count -= 1;
+            }
+
+            self.listings.remove(nfgid)
+        }
+
+        /// Calls the listing hook of each Royalty NFT collection's royalty component so the creator can react to their NFTs
+        /// being listed, auctioned, bundled or offered in a swap - and to them being sold. It isn't called when an NFT is returned
+        /// to its owner (cancelled, expired or unsold), so a failing hook can't hold NFTs in escrow. The hook is opt-in: it's only called
+        /// for collections with "listing_hook" set to true in their resource metadata, as royalty components aren't required
+        /// to have an update_listing_state method. The NFTs are grouped by collection, so a batch only calls each royalty
+        /// component once.
+        fn update_listing_states(&self, nfgids: &[NonFungibleGlobalId], listed: bool) {
+            let mut collections: IndexMap<ResourceAddress, Vec<NonFungibleGlobalId>> =
+                IndexMap::new();
+
+            for nfgid in nfgids {
+                collections
+                    .entry(nfgid.resource_address())
+                    .or_default()
+                    .push(nfgid.clone());
+            }
+
+            for (nft_address, nfts) in collections {
+                let listing_hook: Option<bool> = ResourceManager::from_address(nft_address)
+                    .get_metadata("listing_hook")
+                    .unwrap_or(None);
+
+                if listing_hook != Some(true) {
+                    continue;
+                }
+
+                if let Some(royalty_component) = self.royalty_component_of(nft_address) {
+                    self.royal_admin.as_fungible().authorize_with_amount(1, || {
+                        royalty_component
+                            .call_raw::<()>("update_listing_state", scrypto_args!(nfts, listed));
+                    });
+                }
+            }
+        }
+
+        // utility methods
+
+        pub fn get_listing(&self, nfgid: NonFungibleGlobalId) -> Option<Listing> {
+            self.listings.get(&nfgid).map(|listing| listing.clone())
+        }
+
+        /// Returns up to limit listings, starting from a position in the listing index.
+        pub fn get_listings(&self, start: u64, limit: u64) -> Vec<Listing> {
+            let end = start.saturating_add(limit).min(self.listing_count);
+
+            (start..end)
+                .filter_map(|position| {
+                    let nfgid = self.listing_index.get(&position)?.clone();
+                    self.listings.get(&nfgid).map(|listing| listing.clone())
+                })
+                .collect()
+        }
+
+        pub fn listing_count(&self) -> u64 {
+            self.listing_count
+        }
+
+        pub fn is_listed(&self, nfgid: NonFungibleGlobalId) -> bool {
+            self.listings.get(&nfgid).is_some()
+        }
+
+        pub fn fetch_auth_key(&self) -> (ResourceAddress, NonFungibleLocalId) {
+            (self.auth_key_resource, self.auth_key_local.clone())
+        }
+
+        /// Replaces the trader key that controls this account. Only the OpenHub that created the account can call this,
+        /// once the user has proven they own the linked account.
+        pub fn recover_auth_key(&mut self, new_auth_key: NonFungibleGlobalId) {
+            Runtime::global_component().set_role("admin", rule!(require(new_auth_key.clone())));
+
+            let (auth_key_resource, auth_key_local) = new_auth_key.into_parts();
+
+            self.auth_key_resource = auth_key_resource;
+            self.auth_key_local = auth_key_local;
         }
     }
 }
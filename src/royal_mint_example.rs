@@ -1,3 +1,4 @@
+use scrypto::component::AccountLocker;
 use scrypto::prelude::*;
 
 /// Overview
@@ -51,12 +52,52 @@ struct RoyaltyConfig {
     /// However, if a user wants to turn this off, we need to still allow permissioned dapps to interact with the NFTs/send them back to users.
     /// If set true, then the deposit_via_router can only be used for dapps with explicit permission in the permission dapps keyvalue.
     limit_private_trade: bool,
+    /// Swaps exchange NFTs without a sale price for a % royalty to be taken from, so a creator can block their NFTs
+    /// from being swapped entirely. This starts with the same setting as limit_private_trade.
+    limit_swaps: bool,
+    /// A flat royalty (currency, amount) paid for each NFT swapped. If not set, NFTs can be swapped for free when swaps are allowed.
+    swap_royalty: Option<(ResourceAddress, Decimal)>,
+    /// How the royalty on a sale is calculated from the royalty percentage and the flat royalty amounts.
+    royalty_model: RoyaltyModel,
+    /// Flat royalty amounts for each currency, used by every royalty model except Percent.
+    flat_royalty_amounts: KeyValueStore<ResourceAddress, Decimal>,
+    /// Splits each royalty between several payees (e.g. members of a team) by share in basis points (10000 = 100%).
+    /// If empty, royalties go to the payout account or the royalty vaults as usual.
+    royalty_splits: Vec<(RoyaltyPayee, u16)>,
+    /// Royalty percentages for individual NFTs that replace royalty_percent for them (e.g. 1/1 legendaries at 10%, commons at 3%).
+    /// Overrides can't be negative or above the maximum royalty percentage. As they only replace the percentage, they have
+    /// no effect on sales under the Flat royalty model.
+    royalty_overrides: KeyValueStore<NonFungibleLocalId, Decimal>,
     /// lock royalty configuration: Option can give traders confidence that the royalty percentage/settings will not change.
     /// There's no method to undo this once set to true. However, right now creators can always take steps to make their
     /// royalties more relaxed even if locked - i.e. remove mininimum royalties, allow all buyers, etc.
     royalty_configuration_locked: bool,
 }
 
+/// Someone who receives a share of the royalties when a split is set.
+#[derive(ScryptoSbor, Clone, PartialEq, Eq, Debug)]
+pub enum RoyaltyPayee {
+    /// The share is sent straight to the account via the AccountLocker.
+    Account(Global<Account>),
+    /// The share is held by the component until the holder of the badge claims it.
+    Badge(NonFungibleGlobalId),
+}
+
+/// The ways a royalty can be calculated on a sale. Whichever model is used, the royalty can never be more than
+/// the maximum royalty percentage of the sale price.
+#[derive(ScryptoSbor, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoyaltyModel {
+    /// A percentage of the sale price (royalty_percent).
+    Percent,
+    /// A flat amount for each sale in the currency used. Sales are only possible in currencies with a flat amount set.
+    /// Royalty overrides don't apply, as no percentage is used.
+    Flat,
+    /// Whichever is greater of the percentage and the flat amount - a floor on the royalty for low priced sales.
+    GreaterOf,
+    /// Whichever is lesser of the percentage and the flat amount - a cap on the royalty for high priced sales.
+    LesserOf,
+}
+
 #[derive(ScryptoSbor, NonFungibleData)]
 struct NFT {
     #[mutable]
@@ -67,6 +108,20 @@ struct NFT {
     key_image_url: Url,
     #[mutable]
     attributes: Vec<HashMap<String, String>>,
+    // Reactive data - only updated by the royalty component if the creator has turned reactive data on.
+    /// The number of times the NFT has been sold through OpenTrade
+    #[mutable]
+    sale_count: u64,
+    /// The price and currency of the NFT's most recent sale
+    #[mutable]
+    last_sale: Option<(Decimal, ResourceAddress)>,
+    /// The time of the NFT's most recent sale
+    #[mutable]
+    last_sale_time: Option<Instant>,
+    /// Whether the NFT is currently on offer from an OpenTrader account - listed, auctioned, bundled or in a swap.
+    /// It's cleared when the NFT is sold, but not when an offer is cancelled or expires.
+    #[mutable]
+    listed: bool,
 }
 
 #[derive(ScryptoSbor, NonFungibleData)]
@@ -97,6 +152,7 @@ mod royal_nft {
     enable_method_auth! {
     roles {
         admin => updatable_by: [];
+        trader => updatable_by: [];
     },
     methods {
         mint_preview_nft => PUBLIC;
@@ -106,6 +162,9 @@ mod royal_nft {
         creator_admin => PUBLIC;
         mint_reveal => PUBLIC;
         pay_royalty => PUBLIC;
+        pay_nft_royalty => PUBLIC;
+        pay_swap_royalty => PUBLIC;
+        swap_royalty => PUBLIC;
         transfer_royalty_nft_to_dapp => PUBLIC;
         change_royalty_percentage_fee => restrict_to: [admin];
         lower_maximum_royalty_percentage => restrict_to: [admin];
@@ -126,10 +185,35 @@ mod royal_nft {
         allow_all_buyers => restrict_to: [admin];
         deny_all_buyers => restrict_to: [admin];
         lock_royalty_configuration => restrict_to: [admin];
+        limit_swaps_true => restrict_to: [admin];
+        limit_swaps_false => restrict_to: [admin];
+        set_swap_royalty => restrict_to: [admin];
+        remove_swap_royalty => restrict_to: [admin];
+        set_royalty_model => restrict_to: [admin];
+        set_flat_royalty_amount => restrict_to: [admin];
+        remove_flat_royalty_amount => restrict_to: [admin];
+        set_royalty_splits => restrict_to: [admin];
+        set_royalty_override => restrict_to: [admin];
+        remove_royalty_override => restrict_to: [admin];
+        royalty_percent => PUBLIC;
+        enable_reactive_data => restrict_to: [admin];
+        disable_reactive_data => restrict_to: [admin];
+        update_listing_state => restrict_to: [trader];
+        claim_split_royalties => PUBLIC;
+        split_royalty_balance => PUBLIC;
         resource_address => PUBLIC;
         deposit_via_router => PUBLIC;
         add_virtual_account_admin => restrict_to: [admin];
         remove_virtual_account_admin => restrict_to: [admin];
+        set_mint_price => restrict_to: [admin];
+        remove_mint_price => restrict_to: [admin];
+        get_mint_prices => PUBLIC;
+        withdraw_mint_proceeds => restrict_to: [admin];
+        withdraw_all_mint_proceeds => restrict_to: [admin];
+        withdraw_royalties => restrict_to: [admin];
+        withdraw_all_royalties => restrict_to: [admin];
+        set_royalty_payout_account => restrict_to: [admin];
+        royalty_balance => PUBLIC;
     }
     }
 
@@ -149,11 +233,8 @@ mod royal_nft {
         // the admin address input required to sync with the OpenTrader system
         depositer_admin: ResourceAddress,
 
-        /// The price to mint a Royal NFT NFT
-        mint_price: Decimal,
-
-        /// The selected currrency for minting Royal NFTs, e.g. XRD
-        mint_currency: ResourceAddress,
+        /// The price to mint a Royal NFT in each accepted currency, e.g. 100 XRD or 5 USDC
+        mint_prices: HashMap<ResourceAddress, Decimal>,
 
         /// The maximum number of Royal NFTs that can be minted
         collection_cap: u64,
@@ -161,12 +242,30 @@ mod royal_nft {
         /// The current mint ID for integer NFTs minted
         mint_id: u64,
 
-        /// The vault for storing mint payments
-        mint_payments_vault: Vault,
+        /// The vaults for storing mint payments for each currency
+        mint_payments_vaults: KeyValueStore<ResourceAddress, Vault>,
+
+        /// The currencies that mint payment vaults have been created for - used to sweep all mint proceeds in one call
+        mint_payment_currencies: Vec<ResourceAddress>,
 
         /// All the royalty payments that have been made for different currencies
         royalty_vaults: KeyValueStore<ResourceAddress, Vault>,
 
+        /// The currencies that royalty vaults have been created for - used to sweep all royalties in one call
+        royalty_currencies: Vec<ResourceAddress>,
+
+        /// An optional account that royalties are forwarded to as soon as they are paid, rather than being held in the royalty vaults
+        royalty_payout_account: Option<Global<Account>>,
+
+        /// The AccountLocker used to forward royalties to the payout account and to account payees of a royalty split
+        royalty_locker: Global<AccountLocker>,
+
+        /// The royalty shares held for badge payees of a royalty split until they are claimed
+        split_royalty_vaults: KeyValueStore<(NonFungibleGlobalId, ResourceAddress), Vault>,
+
+        /// The currencies each badge payee has been paid a royalty share in - used to claim all shares in one call
+        split_royalty_currencies: KeyValueStore<NonFungibleGlobalId, Vec<ResourceAddress>>,
+
         /// The address of the royalty component (which in this case, is this same component)
         royalty_component: ComponentAddress,
 
@@ -176,6 +275,12 @@ mod royal_nft {
         /// virtual_account temp admin
         virtual_account_admin: Option<Global<Account>>,
 
+        /// Whether NFT data can be updated by this component - set by the metadata_updatable rule at instantiation
+        metadata_updatable: bool,
+
+        /// Opt-in for the reactive NFT data (sale count, last sale, listing state) to be updated on sales and listings
+        reactive_data: bool,
+
         /// Specify minting venue/marketplace - i.e. specific marketplaces that can mint the NFTs.
         /// This is useful if a creator wants to allow minting of their NFTs on a specific marketplace.
         minting_venue: KeyValueStore<ResourceAddress, ()>,
@@ -192,6 +297,7 @@ mod royal_nft {
             preview_image_url: String,
 
             // generic minting inputs (could be any set up for minting the collection)
+            // further mint currencies can be added to the price table after instantiation
             mint_price: Decimal,
             mint_currency: ResourceAddress,
             collection_cap: u64,
@@ -289,6 +395,12 @@ mod royal_nft {
                 limit_currencies: limits[1],
                 limit_dapps: limits[2],
                 limit_private_trade: limits[3],
+                limit_swaps: limits[3],
+                swap_royalty: None,
+                royalty_model: RoyaltyModel::Percent,
+                flat_royalty_amounts: KeyValueStore::new(),
+                royalty_splits: vec![],
+                royalty_overrides: KeyValueStore::new(),
                 minimum_royalties: limits[4],
                 permitted_currencies,
                 minimum_royalty_amounts,
@@ -401,7 +513,11 @@ mod royal_nft {
                 roles {
                     metadata_locker => creator_admin_rule.clone();
                     metadata_locker_updater => creator_admin_rule.clone();
-                    metadata_setter => creator_admin_rule.clone();
+                    // The component sets the listing_hook metadata when reactive data is turned on or off.
+                    metadata_setter => rule!(
+                        require_amount(1, nft_creator_admin.resource_address())
+                            || require(global_caller(royalty_component_address))
+                    );
                     metadata_setter_updater => creator_admin_rule;
                 },
                 init {
@@ -416,7 +532,8 @@ mod royal_nft {
                     // It's important we don't place this component address on the individual NFTs because
                     // that would require us knowing the exact NFT Metadata structure to fetch/handle this data within Scrypto.
                     "royalty_component" => royalty_component_address, updatable;
-
+                    // Trader accounts only call the update_listing_state hook of collections that set this to true.
+                    "listing_hook" => false, updatable;
 
                 }
             })
@@ -426,6 +543,16 @@ mod royal_nft {
 
             let virtual_account_admin: Option<Global<Account>> = None;
 
+            // The locker is only ever stored to by this component when forwarding royalties to the creator's payout account.
+            let royalty_locker = Blueprint::<AccountLocker>::instantiate(
+                OwnerRole::None,
+                global_caller_badge_rule.clone(),
+                rule!(deny_all),
+                rule!(deny_all),
+                rule!(deny_all),
+                None,
+            );
+
             let component_adresss = Self {
                 nft_manager,
                 royalty_component: royalty_component_address,
@@ -437,14 +564,21 @@ mod royal_nft {
                 initial_sale_cap: 0,
                 metadata: KeyValueStore::new(),
                 depositer_admin,
-                mint_price,
-                mint_currency: mint_currency.clone(),
+                mint_prices: hashmap!(mint_currency => mint_price),
                 collection_cap,
                 mint_id: 0,
-                mint_payments_vault: Vault::new(mint_currency),
+                mint_payments_vaults: KeyValueStore::new(),
+                mint_payment_currencies: vec![],
                 royalty_vaults: KeyValueStore::new(),
+                royalty_currencies: vec![],
+                royalty_payout_account: None,
+                royalty_locker,
+                split_royalty_vaults: KeyValueStore::new(),
+                split_royalty_currencies: KeyValueStore::new(),
                 royalty_config,
                 virtual_account_admin,
+                metadata_updatable: rules[2],
+                reactive_data: false,
                 minting_venue: KeyValueStore::new(),
             }
             .instantiate()
@@ -462,10 +596,13 @@ mod royal_nft {
                     "description" => "An NFT minting and royalty component.".to_owned(), locked;
                     "dapp_definition" => royalty_component_address, locked;
                     "icon_url" => Url::of(icon_url), locked;
+                    // Trader accounts read this to call pay_nft_royalty rather than the original pay_royalty.
+                    "royalty_interface_version" => 2u32, locked;
                 }
             ))
             .roles(roles!(
                 admin => rule!(require(nft_creator_admin.resource_address()));
+                trader => rule!(require(depositer_admin));
             ))
             .globalize();
 
@@ -496,6 +633,10 @@ mod royal_nft {
                     description: self.description.to_string(),
                     key_image_url: key_image,
                     attributes: metadata.1.clone(),
+                    sale_count: 0,
+                    last_sale: None,
+                    last_sale_time: None,
+                    listed: false,
                 };
 
                 let mint = self.nft_manager.mint_non_fungible(&nft_id, nft);
@@ -532,14 +673,15 @@ mod royal_nft {
                 self.reveal_step == true,
                 "[Mint Reveal] : This NFT doesn't have a reveal step enabled"
             );
+            let mint_price = *self
+                .mint_prices
+                .get(&payment.resource_address())
+                .expect("[Mint Preview NFT] : Incorrect currency to mint NFT");
+
             assert!(
-                payment.amount() >= self.mint_price,
+                payment.amount() >= mint_price,
                 "[Mint Preview NFT] : Insufficient funds to mint NFT"
             );
-            assert!(
-                payment.resource_address() == self.mint_currency,
-                "[Mint Preview NFT] : Incorrect currency to mint NFT"
-            );
 
             assert!(
                 self.mint_id < self.collection_cap,
@@ -553,13 +695,17 @@ mod royal_nft {
                 "Permission is required to mint this NFT"
             );
 
-            self.mint_payments_vault.put(payment.take(self.mint_price));
+            self.deposit_mint_payment(payment.take(mint_price));
 
             let nft = NFT {
                 name: self.mint_id.to_string(),
                 description: self.description.to_string(),
                 key_image_url: Url::of(self.preview_image_url.clone()),
                 attributes: vec![],
+                sale_count: 0,
+                last_sale: None,
+                last_sale_time: None,
+                listed: false,
             };
 
             let edition = self.mint_id.to_string();
@@ -601,6 +747,82 @@ mod royal_nft {
             vec![payment, receipt.into()]
         }
 
+        fn deposit_mint_payment(&mut self, mint_payment: Bucket) {
+            let currency = mint_payment.resource_address();
+
+            let vault_exists = self.mint_payments_vaults.get(&currency).is_some();
+
+            if vault_exists {
+                self.mint_payments_vaults
+                    .get_mut(&currency)
+                    .unwrap()
+                    .put(mint_payment);
+            } else {
+                self.mint_payments_vaults
+                    .insert(currency, Vault::with_bucket(mint_payment));
+                self.mint_payment_currencies.push(currency);
+            }
+        }
+
+        /// Sets the price to mint in a currency - adding the currency to the price table if it isn't already accepted.
+        pub fn set_mint_price(&mut self, currency: ResourceAddress, price: Decimal) {
+            assert!(
+                price >= Decimal::zero(),
+                "[set_mint_price] Mint price can not be negative"
+            );
+
+            self.mint_prices.insert(currency, price);
+        }
+
+        /// Stops accepting a currency for minting. Any proceeds already received in the currency can still be withdrawn.
+        pub fn remove_mint_price(&mut self, currency: ResourceAddress) {
+            assert!(
+                self.mint_prices.len() > 1,
+                "[remove_mint_price] At least one mint currency must be accepted"
+            );
+
+            self.mint_prices
+                .remove(&currency)
+                .expect("[remove_mint_price] Currency is not accepted for minting");
+        }
+
+        pub fn get_mint_prices(&self) -> HashMap<ResourceAddress, Decimal> {
+            self.mint_prices.clone()
+        }
+
+        /// Withdraws an amount of mint proceeds in a given currency.
+        pub fn withdraw_mint_proceeds(
+            &mut self,
+            currency: ResourceAddress,
+            amount: Decimal,
+        ) -> Bucket {
+            let mut vault = self.mint_payments_vaults.get_mut(&currency).expect(
+                "[withdraw_mint_proceeds] No mint payments have been made in this currency",
+            );
+
+            assert!(
+                amount <= vault.amount(),
+                "[withdraw_mint_proceeds] Insufficient mint proceeds to withdraw"
+            );
+
+            vault.take(amount)
+        }
+
+        /// Withdraws all mint proceeds across every currency they have been paid in.
+        pub fn withdraw_all_mint_proceeds(&mut self) -> Vec<Bucket> {
+            let mut proceeds: Vec<Bucket> = vec![];
+
+            for currency in self.mint_payment_currencies.iter() {
+                let mut vault = self.mint_payments_vaults.get_mut(currency).unwrap();
+
+                if !vault.is_empty() {
+                    proceeds.push(vault.take_all());
+                }
+            }
+
+            proceeds
+        }
+
         pub fn add_virtual_account_admin(&mut self, account: Global<Account>) {
             self.virtual_account_admin = Some(account);
         }
@@ -687,24 +909,120 @@ mod royal_nft {
         // This function can be called by trader accounts when an NFT from this collection is purchased.
         // It takes the payment and an option for an account to send the NFT to.
         // It uses the royalty percentage set by the creator to determine how much of the payment to take.
+        // The full global id of the NFT sold and its sale price are passed so that royalties can be set per NFT.
+        // Trader accounts use this method when the component's "royalty_interface_version" metadata is 2 or more.
         // We use a keyvaluestore of vaults so that we can store multiple currencies.
         // We take the NFT as an argument so that we can determine at this point whether we want to enforce advanced royalties settings
         // where only an account component can own the NFT - in which case we just sent the NFT directly to the input account.
         // Otherwise, we send the NFT back to the trading account component, where a it could be sent on to another component.
+        pub fn pay_nft_royalty(
+            &mut self,
+            nft: NonFungibleGlobalId,
+            price: Decimal,
+            mut payment: Bucket,
+            buyer: ResourceAddress,
+        ) -> Bucket {
+            // check the correct NFT for this royalty component has been passed
+            assert!(
+                nft.resource_address() == self.nft_manager.address(),
+                "[pay_nft_royalty] Incorrect resource passed"
+            );
+
+            assert!(
+                payment.amount() >= price,
+                "[pay_nft_royalty] Payment is less than the sale price"
+            );
+
+            let currency = payment.resource_address();
+
+            let royalty = self.take_royalty(Some(nft.local_id()), price, &mut payment, buyer);
+
+            self.distribute_royalty(royalty);
+
+            if self.reactive_data {
+                self.record_sale(nft.local_id(), price, currency);
+            }
+
+            // payment minus royalty returned to the trading account that called this method
+            payment
+        }
+
+        // The original royalty interface, kept for trader accounts and dApps that don't read "royalty_interface_version".
+        // The whole payment is treated as the sale price and the collection's royalty percentage is used, as the NFT sold isn't known.
         pub fn pay_royalty(
             &mut self,
             nft: ResourceAddress,
             mut payment: Bucket,
             buyer: ResourceAddress,
         ) -> Bucket {
-            let payment_amount = payment.amount();
-
-            // check the correct NFT for this royalty component has been passed
             assert!(
                 nft == self.nft_manager.address(),
                 "[pay_royalty] Incorrect resource passed"
             );
 
+            let price = payment.amount();
+
+            let royalty = self.take_royalty(None, price, &mut payment, buyer);
+
+            self.distribute_royalty(royalty);
+
+            payment
+        }
+
+        /// The swap equivalent of pay_nft_royalty - called by a trader account for each royalty NFT that changes hands in a swap.
+        /// The payment should hold the NFT's swap royalty (see swap_royalty), and whatever is left is returned.
+        /// If no swap royalty is set, an empty bucket can be provided.
+        pub fn pay_swap_royalty(
+            &mut self,
+            nft: NonFungibleGlobalId,
+            mut payment: Bucket,
+            trader: ResourceAddress,
+        ) -> Bucket {
+            assert!(
+                nft.resource_address() == self.nft_manager.address(),
+                "[pay_swap_royalty] Incorrect resource passed"
+            );
+
+            assert!(
+                !self.royalty_config.limit_swaps,
+                "[pay_swap_royalty] This NFT can not be swapped"
+            );
+
+            if self.royalty_config.limit_buyers {
+                assert!(
+                    self.royalty_config
+                        .permissioned_buyers
+                        .get(&trader)
+                        .is_some(),
+                    "This buyer is not permissioned to trade this NFT"
+                );
+            }
+
+            if let Some((currency, amount)) = self.swap_royalty(nft.local_id().clone()) {
+                assert!(
+                    payment.resource_address() == currency,
+                    "[pay_swap_royalty] Swap royalty must be paid in {:?}",
+                    currency
+                );
+
+                let royalty =
+                    payment.take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
+
+                self.distribute_royalty(royalty);
+            }
+
+            payment
+        }
+
+        /// Checks the buyer and currency are permitted and takes the royalty due on a sale from the payment.
+        /// If the NFT sold isn't known, the collection's royalty percentage is used.
+        fn take_royalty(
+            &self,
+            nft_local_id: Option<&NonFungibleLocalId>,
+            price: Decimal,
+            payment: &mut Bucket,
+            buyer: ResourceAddress,
+        ) -> Bucket {
             if self.royalty_config.limit_buyers {
                 assert!(
                     self.royalty_config
@@ -728,62 +1046,155 @@ mod royal_nft {
                 );
             }
 
-            // send the royalty to the royalty vault
+            let royalty = payment.take_advanced(
+                self.royalty_amount(nft_local_id, price, currency),
+                WithdrawStrategy::Rounded(RoundingMode::ToZero),
+            );
 
-            let vault_exists = self.royalty_vaults.get(&currency).is_some();
+            // check the correct amount has been passed
+            if limit_currencies {
+                if self.royalty_config.minimum_royalties {
+                    let minimum_royalty = self
+                        .royalty_config
+                        .minimum_royalty_amounts
+                        .get(&currency)
+                        .unwrap();
+                    assert!(
+                        royalty.amount() >= minimum_royalty.clone(),
+                        "Royalty amount is below the minimum required"
+                    );
+                }
+            }
 
-            if !vault_exists {
-                // check the correct amount has been passed
-                let royalty = payment.take_advanced(
-                    payment_amount
-                        .checked_mul(self.royalty_config.royalty_percent)
-                        .unwrap(),
-                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
-                );
+            royalty
+        }
 
-                if limit_currencies {
-                    if self.royalty_config.minimum_royalties {
-                        let minimum_royalty = self
-                            .royalty_config
-                            .minimum_royalty_amounts
-                            .get(&currency)
-                            .unwrap();
-                        assert!(
-                            royalty.amount() >= minimum_royalty.clone(),
-                            "Royalty amount is below the minimum required"
-                        );
-                    }
+        /// Works out the royalty due on a sale of an NFT for the given amount in the given currency using the creator's royalty model.
+        /// The result is capped at the maximum royalty percentage of the sale price.
+        fn royalty_amount(
+            &self,
+            nft_local_id: Option<&NonFungibleLocalId>,
+            payment_amount: Decimal,
+            currency: ResourceAddress,
+        ) -> Decimal {
+            let royalty_percent = match nft_local_id {
+                Some(nft_local_id) => self.royalty_percent(nft_local_id.clone()),
+                None => self.royalty_config.royalty_percent,
+            };
+
+            let percent_royalty = payment_amount.checked_mul(royalty_percent).unwrap();
+
+            let flat_royalty = self
+                .royalty_config
+                .flat_royalty_amounts
+                .get(&currency)
+                .map(|amount| *amount);
+
+            let royalty = match (self.royalty_config.royalty_model, flat_royalty) {
+                (RoyaltyModel::Percent, _) => percent_royalty,
+                (RoyaltyModel::Flat, Some(flat_royalty)) => flat_royalty,
+                (RoyaltyModel::Flat, None) => {
+                    panic!("[pay_royalty] No flat royalty is set for this currency")
                 }
+                (RoyaltyModel::GreaterOf, Some(flat_royalty)) => percent_royalty.max(flat_royalty),
+                (RoyaltyModel::LesserOf, Some(flat_royalty)) => percent_royalty.min(flat_royalty),
+                (_, None) => percent_royalty,
+            };
 
-                self.royalty_vaults
-                    .insert(currency.clone(), Vault::with_bucket(royalty));
-            } else {
-                // check the correct amount has been passed
-                let royalty = payment.take_advanced(
-                    payment_amount
-                        .checked_mul(self.royalty_config.royalty_percent)
-                        .unwrap(),
-                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+            let maximum_royalty = payment_amount
+                .checked_mul(self.royalty_config.maximum_royalty_percent)
+                .unwrap();
+
+            royalty.min(maximum_royalty)
+        }
+
+        /// Listing hook - called by trader accounts when NFTs from this collection are listed, auctioned, bundled or offered in a swap,
+        /// and when they're sold. It isn't called when a listing is cancelled or expires, so an NFT returned to its owner stays
+        /// marked as listed until it's next listed or sold. Trader accounts only call it while the "listing_hook" resource metadata is true, which is set with reactive data.
+        /// A trader account passes all the NFTs of a batch at once. Only trader accounts (holders of the depositer admin badge)
+        /// can call this, and it does nothing unless reactive data is on.
+        pub fn update_listing_state(&mut self, nfts: Vec<NonFungibleGlobalId>, listed: bool) {
+            for nft in nfts.iter() {
+                assert!(
+                    nft.resource_address() == self.nft_manager.address(),
+                    "[update_listing_state] Incorrect resource passed"
                 );
+            }
 
-                if limit_currencies {
-                    if self.royalty_config.minimum_royalties {
-                        let minimum_royalty = self
-                            .royalty_config
-                            .minimum_royalty_amounts
-                            .get(&currency)
-                            .unwrap();
-                        assert!(
-                            royalty.amount() >= minimum_royalty.clone(),
-                            "Royalty amount is below the minimum required"
-                        );
-                    }
+            if self.reactive_data {
+                for nft in nfts {
+                    self.nft_manager
+                        .update_non_fungible_data(nft.local_id(), "listed", listed);
                 }
-                self.royalty_vaults.get_mut(&currency).unwrap().put(royalty);
             }
+        }
 
-            // payment minus royalty returned to the trading account that called this method
-            payment
+        /// Updates the reactive data of an NFT that has just been sold.
+        fn record_sale(
+            &self,
+            nft_local_id: &NonFungibleLocalId,
+            price: Decimal,
+            currency: ResourceAddress,
+        ) {
+            let nft_data: NFT = self.nft_manager.get_non_fungible_data(nft_local_id);
+
+            self.nft_manager.update_non_fungible_data(
+                nft_local_id,
+                "sale_count",
+                nft_data.sale_count + 1,
+            );
+            self.nft_manager.update_non_fungible_data(
+                nft_local_id,
+                "last_sale",
+                Some((price, currency)),
+            );
+            self.nft_manager.update_non_fungible_data(
+                nft_local_id,
+                "last_sale_time",
+                Some(Clock::current_time_rounded_to_seconds()),
+            );
+            self.nft_manager
+                .update_non_fungible_data(nft_local_id, "listed", false);
+        }
+
+        /// The royalty percentage paid on sales of an NFT - its override if one is set, otherwise the collection's royalty percentage.
+        pub fn royalty_percent(&self, nft_local_id: NonFungibleLocalId) -> Decimal {
+            match self.royalty_config.royalty_overrides.get(&nft_local_id) {
+                Some(royalty_percent) => *royalty_percent,
+                None => self.royalty_config.royalty_percent,
+            }
+        }
+
+        /// The flat royalty (currency, amount) paid when an NFT is swapped. An NFT with a royalty override pays the swap royalty
+        /// scaled by its override against the collection's royalty percentage - e.g. an NFT at double the royalty pays double to swap.
+        /// If the collection's royalty percentage is zero there's nothing to scale against, so an NFT with an override pays the swap
+        /// royalty unchanged unless its override is also zero, in which case it swaps for free.
+        pub fn swap_royalty(
+            &self,
+            nft_local_id: NonFungibleLocalId,
+        ) -> Option<(ResourceAddress, Decimal)> {
+            let (currency, amount) = self.royalty_config.swap_royalty?;
+
+            let royalty_override = match self.royalty_config.royalty_overrides.get(&nft_local_id) {
+                Some(royalty_percent) => *royalty_percent,
+                None => return Some((currency, amount)),
+            };
+
+            let collection_royalty_percent = self.royalty_config.royalty_percent;
+
+            let scaled_amount = if royalty_override == Decimal::zero() {
+                Decimal::zero()
+            } else if collection_royalty_percent == Decimal::zero() {
+                amount
+            } else {
+                amount
+                    .checked_mul(royalty_override)
+                    .unwrap()
+                    .checked_div(collection_royalty_percent)
+                    .unwrap()
+            };
+
+            Some((currency, scaled_amount))
         }
 
         /// Possibility to transfer the royalty NFT to a dApp if permissions are set for advanced royalty enforcement - requires the dApp to be permissioned - transfer occurs here.
@@ -891,6 +1302,198 @@ mod royal_nft {
             receipt.into()
         }
 
+        // If the creator has set a royalty split, each payee gets their share - the last payee also receives any rounding dust.
+        // Otherwise, if the creator has set a payout account, the royalty is forwarded straight to it via the AccountLocker.
+        // Failing both, we send the royalty to the royalty vault where it can be withdrawn by the creator.
+        fn distribute_royalty(&mut self, mut royalty: Bucket) {
+            if self.royalty_config.royalty_splits.is_empty() {
+                if let Some(payout_account) = self.royalty_payout_account {
+                    self.royalty_locker.store(payout_account, royalty, true);
+                } else {
+                    self.deposit_royalty(royalty);
+                }
+                return;
+            }
+
+            let total_amount = royalty.amount();
+            let splits = self.royalty_config.royalty_splits.clone();
+            let last_index = splits.len() - 1;
+
+            for (index, (payee, share)) in splits.into_iter().enumerate() {
+                let payee_royalty = if index == last_index {
+                    let remaining_amount = royalty.amount();
+                    royalty.take(remaining_amount)
+                } else {
+                    royalty.take_advanced(
+                        total_amount
+                            .checked_mul(Decimal::from(share))
+                            .unwrap()
+                            .checked_div(Decimal::from(10000))
+                            .unwrap(),
+                        WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                    )
+                };
+
+                if payee_royalty.is_empty() {
+                    payee_royalty.drop_empty();
+                    continue;
+                }
+
+                match payee {
+                    RoyaltyPayee::Account(account) => {
+                        self.royalty_locker.store(account, payee_royalty, true);
+                    }
+                    RoyaltyPayee::Badge(badge) => {
+                        self.deposit_split_royalty(badge, payee_royalty);
+                    }
+                }
+            }
+
+            royalty.drop_empty();
+        }
+
+        fn deposit_split_royalty(&mut self, badge: NonFungibleGlobalId, royalty: Bucket) {
+            let key = (badge.clone(), royalty.resource_address());
+
+            let vault_exists = self.split_royalty_vaults.get(&key).is_some();
+
+            if vault_exists {
+                self.split_royalty_vaults
+                    .get_mut(&key)
+                    .unwrap()
+                    .put(royalty);
+            } else {
+                let currency = key.1;
+                self.split_royalty_vaults
+                    .insert(key, Vault::with_bucket(royalty));
+
+                let currencies_exist = self.split_royalty_currencies.get(&badge).is_some();
+
+                if currencies_exist {
+                    self.split_royalty_currencies
+                        .get_mut(&badge)
+                        .unwrap()
+                        .push(currency);
+                } else {
+                    self.split_royalty_currencies.insert(badge, vec![currency]);
+                }
+            }
+        }
+
+        fn deposit_royalty(&mut self, royalty: Bucket) {
+            let currency = royalty.resource_address();
+
+            let vault_exists = self.royalty_vaults.get(&currency).is_some();
+
+            if vault_exists {
+                self.royalty_vaults.get_mut(&currency).unwrap().put(royalty);
+            } else {
+                self.royalty_vaults
+                    .insert(currency, Vault::with_bucket(royalty));
+                self.royalty_currencies.push(currency);
+            }
+        }
+
+        //
+        // These set of methods allow the creator to collect the royalties that have been paid to the component.
+        //
+
+        /// Withdraws an amount of royalties in a given currency from the royalty vaults.
+        pub fn withdraw_royalties(&mut self, currency: ResourceAddress, amount: Decimal) -> Bucket {
+            let mut vault = self
+                .royalty_vaults
+                .get_mut(&currency)
+                .expect("[withdraw_royalties] No royalties have been paid in this currency");
+
+            assert!(
+                amount <= vault.amount(),
+                "[withdraw_royalties] Insufficient royalties to withdraw"
+            );
+
+            vault.take(amount)
+        }
+
+        /// Withdraws all royalties across every currency they have been paid in.
+        pub fn withdraw_all_royalties(&mut self) -> Vec<Bucket> {
+            let mut royalties: Vec<Bucket> = vec![];
+
+            for currency in self.royalty_currencies.iter() {
+                let mut vault = self.royalty_vaults.get_mut(currency).unwrap();
+
+                if !vault.is_empty() {
+                    royalties.push(vault.take_all());
+                }
+            }
+
+            royalties
+        }
+
+        /// Sets an account that royalties are forwarded to via the AccountLocker as they are paid.
+        /// Passing None stops forwarding and royalties are held in the royalty vaults again.
+        /// Any royalties already in the vaults are not moved and can still be withdrawn.
+        pub fn set_royalty_payout_account(&mut self, payout_account: Option<Global<Account>>) {
+            self.royalty_payout_account = payout_account;
+        }
+
+        /// Claims every royalty share held for the payee badges shown in the proof, across all currencies.
+        /// Each payee claims independently of the creator and the other payees.
+        pub fn claim_split_royalties(&mut self, payee_badge: Proof) -> Vec<Bucket> {
+            let resource_address = payee_badge.resource_address();
+            let local_ids = payee_badge
+                .skip_checking()
+                .as_non_fungible()
+                .non_fungible_local_ids();
+
+            let mut royalties: Vec<Bucket> = vec![];
+
+            for local_id in local_ids {
+                let badge = NonFungibleGlobalId::new(resource_address, local_id);
+
+                let currencies = match self.split_royalty_currencies.get(&badge) {
+                    Some(currencies) => currencies.clone(),
+                    None => continue,
+                };
+
+                for currency in currencies {
+                    let mut vault = self
+                        .split_royalty_vaults
+                        .get_mut(&(badge.clone(), currency))
+                        .unwrap();
+
+                    if !vault.is_empty() {
+                        royalties.push(vault.take_all());
+                    }
+                }
+            }
+
+            assert!(
+                !royalties.is_empty(),
+                "[claim_split_royalties] No royalties to claim for this badge"
+            );
+
+            royalties
+        }
+
+        /// Read-only query of the royalty share held for a badge payee in a currency.
+        pub fn split_royalty_balance(
+            &self,
+            payee_badge: NonFungibleGlobalId,
+            currency: ResourceAddress,
+        ) -> Decimal {
+            match self.split_royalty_vaults.get(&(payee_badge, currency)) {
+                Some(vault) => vault.amount(),
+                None => Decimal::zero(),
+            }
+        }
+
+        /// Read-only query of the royalties held by the component for a currency.
+        pub fn royalty_balance(&self, currency: ResourceAddress) -> Decimal {
+            match self.royalty_vaults.get(&currency) {
+                Some(vault) => vault.amount(),
+                None => Decimal::zero(),
+            }
+        }
+
         //
         // These set of methods offer the ability for the creator modify their royalty settings.
         //
@@ -1065,6 +1668,167 @@ mod royal_nft {
             self.royalty_config.limit_buyers = false;
         }
 
+        // You can't block swaps if the configuration is locked.
+        pub fn limit_swaps_true(&mut self) {
+            assert!(
+                !self.royalty_config.royalty_configuration_locked,
+                "Royalty configuration is locked"
+            );
+            self.royalty_config.limit_swaps = true;
+        }
+
+        // You can allow swaps even if the configuration is locked.
+        pub fn limit_swaps_false(&mut self) {
+            self.royalty_config.limit_swaps = false;
+        }
+
+        // You can't set a swap royalty if the configuration is locked.
+        pub fn set_swap_royalty(&mut self, currency: ResourceAddress, amount: Decimal) {
+            assert!(
+                !self.royalty_config.royalty_configuration_locked,
+                "Royalty configuration is locked"
+            );
+            assert!(
+                amount > Decimal::zero(),
+                "Swap royalty must be greater than zero"
+            );
+            self.royalty_config.swap_royalty = Some((currency, amount));
+        }
+
+        // You can remove the swap royalty even if the configuration is locked.
+        pub fn remove_swap_royalty(&mut self) {
+            self.royalty_config.swap_royalty = None;
+        }
+
+        // Reactive data can only be turned on if the NFT data was made updatable at instantiation.
+        // The listing_hook metadata tells trader accounts to call update_listing_state while reactive data is on.
+        pub fn enable_reactive_data(&mut self) {
+            assert!(
+                self.metadata_updatable,
+                "NFT data is not updatable for this collection"
+            );
+            self.reactive_data = true;
+            self.nft_manager.set_metadata("listing_hook", true);
+        }
+
+        pub fn disable_reactive_data(&mut self) {
+            self.reactive_data = false;
+            self.nft_manager.set_metadata("listing_hook", false);
+        }
+
+        // You can't raise or add a royalty override if the configuration is locked - you can lower one though.
+        // Overrides must be between zero and the maximum royalty percentage.
+        pub fn set_royalty_override(
+            &mut self,
+            nft_local_id: NonFungibleLocalId,
+            royalty_percent: Decimal,
+        ) {
+            assert!(
+                royalty_percent >= Decimal::zero(),
+                "[set_royalty_override] Royalty override can not be negative"
+            );
+
+            assert!(
+                royalty_percent <= self.royalty_config.maximum_royalty_percent,
+                "New royalty percentage is greater than maximum allowed"
+            );
+
+            if self.royalty_config.royalty_configuration_locked {
+                assert!(
+                    royalty_percent <= self.royalty_percent(nft_local_id.clone()),
+                    "Royalty configuration is locked"
+                );
+            }
+
+            self.royalty_config
+                .royalty_overrides
+                .insert(nft_local_id, royalty_percent);
+        }
+
+        // Removing an override returns the NFT to the collection's royalty percentage.
+        // If the configuration is locked, this is only possible when it lowers the NFT's royalty.
+        pub fn remove_royalty_override(&mut self, nft_local_id: NonFungibleLocalId) {
+            if self.royalty_config.royalty_configuration_locked {
+                assert!(
+                    self.royalty_config.royalty_percent
+                        <= self.royalty_percent(nft_local_id.clone()),
+                    "Royalty configuration is locked"
+                );
+            }
+
+            self.royalty_config.royalty_overrides.remove(&nft_local_id);
+        }
+
+        // You can't change the royalty model if the configuration is locked.
+        pub fn set_royalty_model(&mut self, royalty_model: RoyaltyModel) {
+            assert!(
+                !self.royalty_config.royalty_configuration_locked,
+                "Royalty configuration is locked"
+            );
+            self.royalty_config.royalty_model = royalty_model;
+        }
+
+        // You can't raise or add a flat royalty amount if the configuration is locked - you can lower one though.
+        // Flat royalties are still capped by the maximum royalty percentage of the sale price.
+        pub fn set_flat_royalty_amount(&mut self, currency: ResourceAddress, amount: Decimal) {
+            assert!(
+                amount > Decimal::zero(),
+                "Flat royalty amount must be greater than zero"
+            );
+
+            if self.royalty_config.royalty_configuration_locked {
+                let current_amount = self
+                    .royalty_config
+                    .flat_royalty_amounts
+                    .get(&currency)
+                    .map(|amount| *amount);
+                assert!(
+                    current_amount.is_some_and(|current_amount| amount <= current_amount),
+                    "Royalty configuration is locked"
+                );
+            }
+
+            self.royalty_config
+                .flat_royalty_amounts
+                .insert(currency, amount);
+        }
+
+        // You can't remove flat royalty amounts if the configuration is locked, as with the Flat model
+        // that would stop the currency being used for sales.
+        pub fn remove_flat_royalty_amount(&mut self, currency: ResourceAddress) {
+            assert!(
+                !self.royalty_config.royalty_configuration_locked,
+                "Royalty configuration is locked"
+            );
+            self.royalty_config.flat_royalty_amounts.remove(&currency);
+        }
+
+        // You can't change the royalty split if the configuration is locked.
+        // Shares are in basis points and must add up to 10000 - an empty split sends royalties to the creator as usual.
+        pub fn set_royalty_splits(&mut self, royalty_splits: Vec<(RoyaltyPayee, u16)>) {
+            assert!(
+                !self.royalty_config.royalty_configuration_locked,
+                "Royalty configuration is locked"
+            );
+
+            if !royalty_splits.is_empty() {
+                assert!(
+                    royalty_splits.iter().all(|(_, share)| *share > 0),
+                    "Royalty split shares must be greater than zero"
+                );
+                assert!(
+                    royalty_splits
+                        .iter()
+                        .map(|(_, share)| *share as u32)
+                        .sum::<u32>()
+                        == 10000,
+                    "Royalty split shares must add up to 10000 basis points"
+                );
+            }
+
+            self.royalty_config.royalty_splits = royalty_splits;
+        }
+
         pub fn lock_royalty_configuration(&mut self) {
             self.royalty_config.royalty_configuration_locked = true;
         }
@@ -0,0 +1,271 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn outbid_bidder_is_refunded_and_winner_receives_nft() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let first_bidder = make_user(&mut test_runner, Some("Bob"));
+    let second_bidder = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    let first_badge = test_runner.create_fungible_resource(dec!(1), 0, first_bidder.account);
+    let second_badge = test_runner.create_fungible_resource(dec!(1), 0, second_bidder.account);
+
+    let now = current_time(&mut test_runner);
+
+    auction_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        dec!(10),
+        now,
+        now.add_seconds(3600).unwrap(),
+        vec![first_badge, second_badge],
+    );
+
+    let first_balance = test_runner.get_component_balance(first_bidder.account, XRD);
+
+    place_bid(
+        &mut test_runner,
+        &first_bidder,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(100),
+        first_badge,
+        first_bidder.account,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(first_bidder.account, XRD),
+        first_balance - dec!(100)
+    );
+
+    // A new bid must beat the highest bid by the minimum increment.
+    place_bid(
+        &mut test_runner,
+        &second_bidder,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(105),
+        second_badge,
+        second_bidder.account,
+    )
+    .expect_commit_failure();
+
+    place_bid(
+        &mut test_runner,
+        &second_bidder,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(120),
+        second_badge,
+        second_bidder.account,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(first_bidder.account, XRD),
+        first_balance
+    );
+
+    // The auction can't be settled or cancelled while it's running with a bid.
+    settle_auction(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        nfgid.clone(),
+    )
+    .expect_commit_failure();
+
+    cancel_auction(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+    )
+    .expect_commit_failure();
+
+    advance_time(&mut test_runner, 3700);
+
+    let seller_balance = test_runner.get_component_balance(seller.account, XRD);
+
+    settle_auction(
+        &mut test_runner,
+        &first_bidder,
+        seller_trader_component,
+        nfgid,
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, second_bidder.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, XRD),
+        seller_balance + dec!(120)
+    );
+}
+
+#[test]
+fn bid_must_be_placed_by_the_bidding_account_owner() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let bidder = make_user(&mut test_runner, Some("Bob"));
+    let victim = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    let badge = test_runner.create_fungible_resource(dec!(1), 0, bidder.account);
+
+    let now = current_time(&mut test_runner);
+
+    auction_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        dec!(10),
+        now,
+        now.add_seconds(3600).unwrap(),
+        vec![badge],
+    );
+
+    place_bid(
+        &mut test_runner,
+        &bidder,
+        seller_trader_component,
+        nfgid,
+        dec!(100),
+        badge,
+        victim.account,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn auction_without_bids_returns_nft_to_seller() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    let now = current_time(&mut test_runner);
+
+    auction_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        dec!(10),
+        now,
+        now.add_seconds(3600).unwrap(),
+        vec![],
+    );
+
+    assert!(get_component_nflids(&mut test_runner, seller.account, nft_address).is_empty());
+
+    advance_time(&mut test_runner, 3700);
+
+    settle_auction(&mut test_runner, &seller, seller_trader_component, nfgid)
+        .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, seller.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
+
+#[test]
+fn cancel_auction_without_bids() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    let now = current_time(&mut test_runner);
+
+    auction_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        dec!(10),
+        now,
+        now.add_seconds(3600).unwrap(),
+        vec![],
+    );
+
+    cancel_auction(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, seller.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+
+    // The auction is gone once cancelled.
+    settle_auction(&mut test_runner, &seller, seller_trader_component, nfgid)
+        .expect_commit_failure();
+}
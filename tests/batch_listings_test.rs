@@ -0,0 +1,129 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn batch_list_reprice_and_cancel() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 3, None);
+
+    let mut terms: HashMap<NonFungibleGlobalId, ListingTerms> = hashmap!();
+
+    for number in 0..3 {
+        terms.insert(
+            create_global_id(nft_address, number),
+            ListingTerms {
+                currency: XRD,
+                price: dec!(100),
+                permissions: vec![marketplace_key],
+                alternative_prices: hashmap!(),
+                max_marketplace_fee: None,
+            },
+        );
+    }
+
+    // Every NFT in the batch needs terms - the whole batch is rejected otherwise.
+    let mut missing_terms = terms.clone();
+    missing_terms.remove(&create_global_id(nft_address, 2));
+
+    batch_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        to_nflids(0..3),
+        missing_terms,
+    )
+    .expect_commit_failure();
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        0
+    );
+
+    batch_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        to_nflids(0..3),
+        terms,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        3
+    );
+
+    batch_change_price(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        vec![(create_global_id(nft_address, 0), XRD, dec!(50))],
+    );
+
+    let seller_balance_before = test_runner.get_component_balance(seller.account, XRD);
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(50),
+        None,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, XRD),
+        seller_balance_before + dec!(50)
+    );
+
+    batch_cancel_listings(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        vec![
+            create_global_id(nft_address, 1),
+            create_global_id(nft_address, 2),
+        ],
+    );
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        0
+    );
+
+    let seller_nfts = get_component_nflids(&mut test_runner, seller.account, nft_address);
+
+    assert!(seller_nfts.contains(&NonFungibleLocalId::integer(1)));
+    assert!(seller_nfts.contains(&NonFungibleLocalId::integer(2)));
+}
@@ -0,0 +1,334 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use scenario_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn bundle_is_purchased_through_marketplace() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.02));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    let bundle_id = bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(100),
+        vec![marketplace_key],
+    );
+
+    let seller_balance = test_runner.get_component_balance(seller.account, XRD);
+
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        bundle_id,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(1)));
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, XRD),
+        seller_balance + dec!(98)
+    );
+
+    // A bundle can only be purchased once.
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        bundle_id,
+        dec!(100),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn bundle_overpayment_is_returned_as_change() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.02));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    let bundle_id = bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(100),
+        vec![marketplace_key],
+    );
+
+    // Paying below the price is rejected.
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        bundle_id,
+        dec!(99),
+    )
+    .expect_commit_failure();
+
+    let buyer_balance = test_runner.get_component_balance(buyer.account, XRD);
+    let seller_balance = test_runner.get_component_balance(seller.account, XRD);
+
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        bundle_id,
+        dec!(120),
+    )
+    .expect_commit_success();
+
+    // Fees are only taken on the bundle price and the rest of the payment is returned.
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        buyer_balance - dec!(100)
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, XRD),
+        seller_balance + dec!(98)
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &buyer, marketplace_component, XRD),
+        dec!(2)
+    );
+}
+
+#[test]
+fn bundle_requires_a_price() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    try_bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(0),
+        vec![],
+        None,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn cancel_bundle_returns_nfts() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    let bundle_id = bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(100),
+        vec![],
+    );
+
+    assert!(get_component_nflids(&mut test_runner, seller.account, nft_address).is_empty());
+
+    cancel_bundle(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        bundle_id,
+    );
+
+    assert_eq!(
+        get_component_nflids(&mut test_runner, seller.account, nft_address).len(),
+        2
+    );
+}
+
+#[test]
+fn royalty_nft_in_a_bundle_can_not_be_priced_at_zero() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    let nft_address = create_nft_resource(&mut test_runner, &creator, 0, 1, None);
+
+    // Putting the whole price on the standard NFT would skip the royalty.
+    try_split_bundle_list(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        hashmap!(
+            create_global_id(collection.nft_address, 0) => dec!(0),
+            create_global_id(nft_address, 0) => dec!(100),
+        ),
+        vec![collection.marketplace_key],
+    )
+    .expect_commit_failure();
+
+    try_split_bundle_list(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        hashmap!(
+            create_global_id(collection.nft_address, 0) => dec!(10),
+            create_global_id(nft_address, 0) => dec!(90),
+        ),
+        vec![collection.marketplace_key],
+    )
+    .expect_commit_success();
+}
+
+#[test]
+fn bundle_is_purchased_by_a_private_buyer() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+    let outsider = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (buyer_key_resource, buyer_key_local, _buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let (outsider_key_resource, outsider_key_local, _outsider_trader_component) =
+        create_open_trader(&mut test_runner, &outsider, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    let bundle_id = bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(100),
+        vec![],
+    );
+
+    add_bundle_private_buyer(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        bundle_id,
+        NonFungibleGlobalId::new(buyer_key_resource, buyer_key_local.clone()),
+    );
+
+    // Another holder of a trader key from the same hub isn't the targeted buyer.
+    purchase_bundle_with_badge(
+        &mut test_runner,
+        &outsider,
+        seller_trader_component,
+        bundle_id,
+        dec!(100),
+        outsider_key_resource,
+        outsider_key_local,
+    )
+    .expect_commit_failure();
+
+    purchase_bundle_with_badge(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        bundle_id,
+        dec!(100),
+        buyer_key_resource,
+        buyer_key_local,
+    )
+    .expect_commit_success();
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(1)));
+}
@@ -218,3 +218,20 @@ pub fn create_nft_resource(
 
     resaddr
 }
+
+/// Moves the ledger clock forward by a number of seconds and returns the new time.
+pub fn advance_time(test_runner: &mut DefaultLedgerSimulator, seconds: i64) -> Instant {
+    let next_round = Round::of(test_runner.get_consensus_manager_state().round.number() + 1);
+    let timestamp_ms = test_runner.get_current_proposer_timestamp_ms() + seconds * 1000;
+
+    test_runner
+        .advance_to_round_at_timestamp(next_round, timestamp_ms)
+        .expect_commit_success();
+
+    Instant::new(timestamp_ms / 1000)
+}
+
+/// The current ledger time to the second.
+pub fn current_time(test_runner: &mut DefaultLedgerSimulator) -> Instant {
+    Instant::new(test_runner.get_current_proposer_timestamp_ms() / 1000)
+}
@@ -3,11 +3,13 @@ use scrypto_test::prelude::*;
 
 use crate::common::*;
 
+/// Turns on preview minting, permitting holders of the minting venue badge to mint.
 pub fn enable_mint_reveal(
     test_runner: &mut DefaultLedgerSimulator,
     user: &User,
     component: ComponentAddress,
     creator_admin: ResourceAddress,
+    minting_venue: ResourceAddress,
 ) {
     let manifest = ManifestBuilder::new()
         .lock_fee_from_faucet()
@@ -16,7 +18,11 @@ pub fn enable_mint_reveal(
             "create_proof_of_amount",
             manifest_args!(creator_admin, dec!(1)),
         )
-        .call_method(component, "enable_mint_reveal", manifest_args!())
+        .call_method(
+            component,
+            "enable_mint_reveal",
+            manifest_args!(1000u64, vec![minting_venue]),
+        )
         .build();
 
     let receipt = test_runner.execute_manifest(
@@ -31,16 +37,44 @@ pub fn mint_royalty_nft(
     test_runner: &mut DefaultLedgerSimulator,
     user: &User,
     component: ComponentAddress,
+    minting_venue: ResourceAddress,
 ) {
+    let receipt = try_mint_royalty_nft(test_runner, user, component, minting_venue, XRD, dec!(100));
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Mints a preview NFT paying in any currency and returns the receipt so tests can check rejected payments.
+pub fn try_mint_royalty_nft(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    minting_venue: ResourceAddress,
+    currency: ResourceAddress,
+    payment: Decimal,
+) -> TransactionReceipt {
     let manifest = ManifestBuilder::new()
         .lock_fee_from_faucet()
-        .call_method(user.account, "withdraw", manifest_args!(XRD, dec!(100)))
-        .take_all_from_worktop(XRD, "payment")
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(minting_venue, dec!(1)),
+        )
+        .pop_from_auth_zone("venue")
+        .call_method(user.account, "withdraw", manifest_args!(currency, payment))
+        .take_all_from_worktop(currency, "payment")
         .with_name_lookup(|builder, lookup| {
             builder.call_method(
                 component,
                 "mint_preview_nft",
-                manifest_args!(lookup.bucket("payment"), user.account),
+                manifest_args!(
+                    lookup.bucket("payment"),
+                    user.account,
+                    lookup.proof("venue")
+                ),
             )
         })
         .call_method(
@@ -49,15 +83,123 @@ pub fn mint_royalty_nft(
             manifest_args!(ManifestExpression::EntireWorktop),
         )
         .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Sets the mint price in a currency and returns the receipt so tests can check rejected changes.
+pub fn set_mint_price(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+    price: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "set_mint_price", manifest_args!(currency, price))
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Stops accepting a mint currency and returns the receipt so tests can check rejected removals.
+pub fn remove_mint_price(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "remove_mint_price", manifest_args!(currency))
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Withdraws an amount of mint proceeds in one currency and returns the receipt so tests can check rejected withdrawals.
+pub fn withdraw_mint_proceeds(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "withdraw_mint_proceeds",
+            manifest_args!(currency, amount),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn withdraw_all_mint_proceeds(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "withdraw_all_mint_proceeds", manifest_args!())
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
     let receipt = test_runner.execute_manifest(
         manifest,
         vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
     );
 
-    if !receipt.is_commit_success() {
-        println!("{:?}", receipt);
-        panic!("TRANSACTION FAIL");
-    }
+    receipt.expect_commit(true);
 }
 
 pub fn nft_address(
@@ -711,6 +853,117 @@ pub fn lock_royalty_configuration(
     receipt.expect_commit(true);
 }
 
+pub fn royalty_balance(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    currency: ResourceAddress,
+) -> Decimal {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(component, "royalty_balance", manifest_args!(currency))
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn withdraw_all_royalties(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "withdraw_all_royalties", manifest_args!())
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true);
+}
+
+/// Withdraws an amount of royalties in one currency and returns the receipt so tests can check rejected withdrawals.
+pub fn withdraw_royalties(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "withdraw_royalties",
+            manifest_args!(currency, amount),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn set_royalty_payout_account(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    payout_account: Option<ComponentAddress>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_royalty_payout_account",
+            manifest_args!(payout_account),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true);
+}
+
 pub fn transfer_royal_nft_to_component(
     test_runner: &mut DefaultLedgerSimulator,
     user: &User,
@@ -755,3 +1008,332 @@ pub fn transfer_royal_nft_to_component(
 
     receipt.expect_commit(true);
 }
+
+pub fn set_swap_royalty(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+    amount: Decimal,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_swap_royalty",
+            manifest_args!(currency, amount),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true);
+}
+
+#[derive(ManifestSbor, Clone, Copy)]
+pub enum RoyaltyModel {
+    Percent,
+    Flat,
+    GreaterOf,
+    LesserOf,
+}
+
+/// Sets the royalty model. The receipt is returned so tests can check changes rejected by a locked configuration.
+pub fn set_royalty_model(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    royalty_model: RoyaltyModel,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_royalty_model",
+            manifest_args!(royalty_model),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Sets the flat royalty amount of a currency. The receipt is returned so tests can check changes rejected by a
+/// locked configuration.
+pub fn set_flat_royalty_amount(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    currency: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_flat_royalty_amount",
+            manifest_args!(currency, amount),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+#[derive(ManifestSbor, Clone)]
+pub enum RoyaltyPayee {
+    Account(ComponentAddress),
+    Badge(NonFungibleGlobalId),
+}
+
+/// Sets the royalty split. The receipt is returned so tests can check rejected splits.
+pub fn set_royalty_splits(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    royalty_splits: Vec<(RoyaltyPayee, u16)>,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_royalty_splits",
+            manifest_args!(royalty_splits),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn split_royalty_balance(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    payee_badge: NonFungibleGlobalId,
+    currency: ResourceAddress,
+) -> Decimal {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            component,
+            "split_royalty_balance",
+            manifest_args!(payee_badge, currency),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+/// Claims the royalty shares held for a payee badge. The receipt is returned so tests can check rejected claims.
+pub fn claim_split_royalties(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    payee_badge: NonFungibleGlobalId,
+) -> TransactionReceipt {
+    let (badge_resource, badge_local) = payee_badge.into_parts();
+
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_non_fungibles(user.account, badge_resource, [badge_local])
+        .pop_from_auth_zone("payee_badge")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                component,
+                "claim_split_royalties",
+                manifest_args!(lookup.proof("payee_badge")),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Sets the royalty percentage of one NFT. The receipt is returned so tests can check rejected overrides.
+pub fn set_royalty_override(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+    royalty_percent: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(
+            component,
+            "set_royalty_override",
+            manifest_args!(nft_local_id, royalty_percent),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Pays a royalty through the original pay_royalty interface, as older trader accounts do.
+pub fn pay_royalty(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    nft_address: ResourceAddress,
+    payment: Decimal,
+    buyer: ResourceAddress,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                component,
+                "pay_royalty",
+                manifest_args!(nft_address, lookup.bucket("payment"), buyer),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn enable_reactive_data(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "enable_reactive_data", manifest_args!())
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true);
+}
+
+pub fn disable_reactive_data(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    component: ComponentAddress,
+    creator_key: ResourceAddress,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_amount",
+            manifest_args!(creator_key, dec!(1)),
+        )
+        .call_method(component, "disable_reactive_data", manifest_args!())
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true);
+}
+
+/// Mirrors the NFT data of the royalty enforced collection so tests can read its reactive data.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct RoyalNftData {
+    pub name: String,
+    pub description: String,
+    pub key_image_url: Url,
+    pub attributes: Vec<HashMap<String, String>>,
+    pub sale_count: u64,
+    pub last_sale: Option<(Decimal, ResourceAddress)>,
+    pub last_sale_time: Option<Instant>,
+    pub listed: bool,
+}
+
+pub fn royal_nft_data(
+    test_runner: &mut DefaultLedgerSimulator,
+    nft_address: ResourceAddress,
+    number: u64,
+) -> RoyalNftData {
+    test_runner.get_non_fungible_data(nft_address, NonFungibleLocalId::integer(number))
+}
+
+/// Whether trader accounts call the collection's listing hook, read from its "listing_hook" resource metadata.
+pub fn listing_hook(
+    test_runner: &mut DefaultLedgerSimulator,
+    nft_address: ResourceAddress,
+) -> bool {
+    match test_runner.get_metadata(nft_address.into(), "listing_hook") {
+        Some(MetadataValue::Bool(listing_hook)) => listing_hook,
+        _ => false,
+    }
+}
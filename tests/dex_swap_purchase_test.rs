@@ -0,0 +1,246 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn purchase_listing_with_swap_respects_max_input() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &seller, package, dec!(0));
+
+    let token = test_runner.create_fungible_resource(dec!(1000), 18, buyer.account);
+
+    // 2 XRD for each token, so a listing priced at 100 XRD costs 50 tokens.
+    let dex_pool = create_dex_pool(
+        &mut test_runner,
+        &seller,
+        package,
+        token,
+        dec!(2),
+        dec!(1000),
+    );
+
+    set_dex_router(
+        &mut test_runner,
+        &seller,
+        marketplace_component,
+        admin_key,
+        Some(dex_pool),
+    );
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    for number in 0..2 {
+        list(
+            &mut test_runner,
+            &seller,
+            seller_trader_component,
+            seller_key_resource,
+            seller_key_local.clone(),
+            nft_address,
+            NonFungibleLocalId::integer(number),
+            dec!(100),
+            None,
+            vec![marketplace_key],
+        );
+    }
+
+    // The buyer won't spend more than 40 tokens, which isn't enough to cover the price.
+    purchase_with_swap(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        false,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        token,
+        dec!(80),
+        dec!(40),
+    )
+    .expect_commit_failure();
+
+    purchase_with_swap(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        false,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        token,
+        dec!(80),
+        dec!(60),
+    )
+    .expect_commit_success();
+
+    // Only the tokens swapped for the price are spent - the unswapped input and the rest of the payment are returned.
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, token),
+        dec!(950)
+    );
+
+    // A max input above the payment swaps the whole payment rather than failing.
+    purchase_with_swap(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        false,
+        seller_trader_component,
+        create_global_id(nft_address, 1),
+        token,
+        dec!(55),
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, token),
+        dec!(900)
+    );
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert_eq!(buyer_nfts.len(), 2);
+}
+
+#[test]
+fn purchase_royal_listing_with_swap_respects_max_input() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let virtual_badge = fetch_virt_badge(&mut test_runner, &seller, open_hub_component);
+
+    let depositer_badge = fetch_depositer_badge(&mut test_runner, &seller, open_hub_component);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    create_event_listener(&mut test_runner, &seller, package, virtual_badge);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &seller, package, dec!(0));
+
+    let mint_factory = create_mint_factory(&mut test_runner, &seller, package);
+
+    let (royalty_nft_component, creator_key) = create_royalty_nft(
+        &mut test_runner,
+        &seller,
+        mint_factory,
+        defaults_royalty_config(),
+        depositer_badge,
+    );
+
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, seller.account);
+
+    enable_mint_reveal(
+        &mut test_runner,
+        &seller,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &seller,
+        royalty_nft_component,
+        minting_venue,
+    );
+
+    let nft_address = nft_address(&mut test_runner, &seller, royalty_nft_component);
+
+    let token = test_runner.create_fungible_resource(dec!(1000), 18, buyer.account);
+
+    let dex_pool = create_dex_pool(
+        &mut test_runner,
+        &seller,
+        package,
+        token,
+        dec!(2),
+        dec!(1000),
+    );
+
+    set_dex_router(
+        &mut test_runner,
+        &seller,
+        marketplace_component,
+        admin_key,
+        Some(dex_pool),
+    );
+
+    list_royalty_nft(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    purchase_with_swap(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        true,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        token,
+        dec!(80),
+        dec!(40),
+    )
+    .expect_commit_failure();
+
+    let royalties_before = royalty_balance(&mut test_runner, &seller, royalty_nft_component, XRD);
+
+    purchase_with_swap(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        true,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        token,
+        dec!(80),
+        dec!(60),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, token),
+        dec!(950)
+    );
+
+    // The royalty is paid in the listing's currency from the swapped payment.
+    assert_eq!(
+        royalty_balance(&mut test_runner, &seller, royalty_nft_component, XRD),
+        royalties_before + dec!(10)
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, buyer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
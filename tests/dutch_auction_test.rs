@@ -0,0 +1,110 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader::open_trader_account;
+use trader_manifests::*;
+
+fn price_curve(decay: open_trader_account::PriceDecay) -> open_trader_account::DutchAuction {
+    open_trader_account::DutchAuction {
+        start_price: dec!(100),
+        floor_price: dec!(50),
+        start_time: Instant::new(1_000),
+        end_time: Instant::new(2_000),
+        decay,
+    }
+}
+
+#[test]
+fn linear_price_decays_from_start_to_floor() {
+    let curve = price_curve(open_trader_account::PriceDecay::Linear);
+
+    assert_eq!(curve.price_at(Instant::new(500)), dec!(100));
+    assert_eq!(curve.price_at(Instant::new(1_000)), dec!(100));
+    assert_eq!(curve.price_at(Instant::new(1_250)), dec!(87.5));
+    assert_eq!(curve.price_at(Instant::new(1_500)), dec!(75));
+    assert_eq!(curve.price_at(Instant::new(2_000)), dec!(50));
+    assert_eq!(curve.price_at(Instant::new(5_000)), dec!(50));
+}
+
+#[test]
+fn stepwise_price_only_falls_at_the_end_of_each_step() {
+    let curve = price_curve(open_trader_account::PriceDecay::Stepwise(200));
+
+    assert_eq!(curve.price_at(Instant::new(1_000)), dec!(100));
+    assert_eq!(curve.price_at(Instant::new(1_199)), dec!(100));
+    assert_eq!(curve.price_at(Instant::new(1_200)), dec!(90));
+    assert_eq!(curve.price_at(Instant::new(1_599)), dec!(80));
+    assert_eq!(curve.price_at(Instant::new(1_999)), dec!(60));
+    assert_eq!(curve.price_at(Instant::new(2_000)), dec!(50));
+    assert_eq!(curve.price_at(Instant::new(5_000)), dec!(50));
+}
+
+#[test]
+fn dutch_listing_is_purchased_at_current_price_with_change() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let now = current_time(&mut test_runner);
+
+    dutch_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        DutchAuction {
+            start_price: dec!(100),
+            floor_price: dec!(50),
+            start_time: now,
+            end_time: now.add_seconds(1_000).unwrap(),
+            decay: PriceDecay::Linear,
+        },
+        vec![marketplace_key],
+        None,
+    );
+
+    advance_time(&mut test_runner, 500);
+
+    let buyer_balance = test_runner.get_component_balance(buyer.account, XRD);
+
+    // The buyer pays the start price and receives the difference to the current price back.
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        buyer_balance - dec!(75)
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, buyer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
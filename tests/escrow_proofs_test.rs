@@ -0,0 +1,128 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn listed_nfts_can_be_proven_while_in_escrow() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    for number in 0..2 {
+        list(
+            &mut test_runner,
+            &seller,
+            seller_trader_component,
+            seller_key_resource,
+            seller_key_local.clone(),
+            nft_address,
+            NonFungibleLocalId::integer(number),
+            dec!(100),
+            None,
+            vec![marketplace_key],
+        );
+    }
+
+    create_listed_nft_proofs(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        vec![
+            create_global_id(nft_address, 0),
+            create_global_id(nft_address, 1),
+        ],
+    )
+    .expect_commit_success();
+
+    // The NFTs never leave escrow, so they stay listed and can still be purchased.
+    assert!(is_listed(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        create_global_id(nft_address, 0)
+    ));
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    // A sold NFT can no longer be proven by the seller.
+    create_listed_nft_proofs(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        vec![create_global_id(nft_address, 0)],
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn only_the_owner_can_prove_listed_nfts() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let outsider = make_user(&mut test_runner, Some("Mallory"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (outsider_key_resource, outsider_key_local, _outsider_trader_component) =
+        create_open_trader(&mut test_runner, &outsider, open_hub_component);
+
+    let (_marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    create_listed_nft_proofs(
+        &mut test_runner,
+        &outsider,
+        seller_trader_component,
+        outsider_key_resource,
+        outsider_key_local,
+        vec![create_global_id(nft_address, 0)],
+    )
+    .expect_commit_failure();
+}
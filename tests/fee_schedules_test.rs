@@ -0,0 +1,262 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+/// Lists NFTs 0..amount of a new collection at 100 XRD on the marketplace, returning the collection.
+fn list_collection(
+    test_runner: &mut DefaultLedgerSimulator,
+    seller: &User,
+    open_hub_component: ComponentAddress,
+    marketplace_key: ResourceAddress,
+    amount: u64,
+) -> (ResourceAddress, ComponentAddress) {
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(test_runner, seller, open_hub_component);
+
+    let nft_address = create_nft_resource(test_runner, seller, 0, amount, None);
+
+    for number in 0..amount {
+        list(
+            test_runner,
+            seller,
+            seller_trader_component,
+            seller_key_resource,
+            seller_key_local.clone(),
+            nft_address,
+            NonFungibleLocalId::integer(number),
+            dec!(100),
+            None,
+            vec![marketplace_key],
+        );
+    }
+
+    (nft_address, seller_trader_component)
+}
+
+#[test]
+fn collection_fee_discounts_the_marketplace_fee() {
+    let (mut test_runner, operator, package) = setup_for_test();
+
+    let seller = make_user(&mut test_runner, Some("Bob"));
+    let buyer = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &operator, package);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &operator, package, dec!(0.05));
+
+    let (nft_address, seller_trader_component) = list_collection(
+        &mut test_runner,
+        &seller,
+        open_hub_component,
+        marketplace_key,
+        1,
+    );
+
+    // Sellers agree to the published marketplace fee, so a collection fee can't raise it.
+    set_collection_fee(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        admin_key,
+        nft_address,
+        dec!(0.1),
+    )
+    .expect_commit_failure();
+
+    set_collection_fee(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        admin_key,
+        nft_address,
+        dec!(0.01),
+    )
+    .expect_commit_success();
+
+    let seller_balance = test_runner.get_component_balance(seller.account, XRD);
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(1)
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, XRD),
+        seller_balance + dec!(99)
+    );
+}
+
+#[test]
+fn volume_tier_applies_to_proven_buyers() {
+    let (mut test_runner, operator, package) = setup_for_test();
+
+    let seller = make_user(&mut test_runner, Some("Bob"));
+    let buyer = make_user(&mut test_runner, Some("Carol"));
+    let outsider = make_user(&mut test_runner, Some("Mallory"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &operator, package);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &operator, package, dec!(0.05));
+
+    let (nft_address, seller_trader_component) = list_collection(
+        &mut test_runner,
+        &seller,
+        open_hub_component,
+        marketplace_key,
+        4,
+    );
+
+    set_volume_tiers(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        admin_key,
+        vec![(1, dec!(0.01))],
+    );
+
+    // Only the owner of an account can claim its tier.
+    purchase_listing_as_buyer(
+        &mut test_runner,
+        &outsider,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        buyer.account,
+    )
+    .expect_commit_failure();
+
+    // The first purchase is at the marketplace fee and the next at the tier rate.
+    purchase_listing_as_buyer(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        buyer.account,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(5)
+    );
+
+    purchase_listing_as_buyer(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 1),
+        dec!(100),
+        buyer.account,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(6)
+    );
+
+    // Without a proven buyer no tier applies.
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 2),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(11)
+    );
+}
+
+#[test]
+fn promotion_waives_the_fee_until_it_ends() {
+    let (mut test_runner, operator, package) = setup_for_test();
+
+    let seller = make_user(&mut test_runner, Some("Bob"));
+    let buyer = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &operator, package);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &operator, package, dec!(0.05));
+
+    let (nft_address, seller_trader_component) = list_collection(
+        &mut test_runner,
+        &seller,
+        open_hub_component,
+        marketplace_key,
+        2,
+    );
+
+    let now = current_time(&mut test_runner);
+
+    add_promotion(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        admin_key,
+        now,
+        now.add_seconds(3600).unwrap(),
+    );
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(0)
+    );
+
+    advance_time(&mut test_runner, 3600);
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 1),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(5)
+    );
+}
@@ -0,0 +1,85 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use misc_manifests::*;
+
+#[test]
+fn hub_resolves_accounts_traders_and_keys() {
+    let (mut test_runner, alice, package) = setup_for_test();
+
+    let bob = make_user(&mut test_runner, Some("Bob"));
+    let carol = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &alice, package);
+
+    assert_eq!(
+        total_accounts(&mut test_runner, &alice, open_hub_component),
+        0
+    );
+
+    let (_alice_key_resource, alice_key_local, alice_trader_component) =
+        create_open_trader(&mut test_runner, &alice, open_hub_component);
+
+    let (_bob_key_resource, bob_key_local, bob_trader_component) =
+        create_open_trader(&mut test_runner, &bob, open_hub_component);
+
+    assert_eq!(
+        total_accounts(&mut test_runner, &alice, open_hub_component),
+        2
+    );
+
+    assert_eq!(
+        get_trader_for_account(&mut test_runner, &alice, open_hub_component, alice.account),
+        Some(alice_trader_component)
+    );
+    assert_eq!(
+        get_trader_for_account(&mut test_runner, &alice, open_hub_component, bob.account),
+        Some(bob_trader_component)
+    );
+    assert_eq!(
+        get_trader_for_account(&mut test_runner, &alice, open_hub_component, carol.account),
+        None
+    );
+
+    assert_eq!(
+        get_account_for_trader(
+            &mut test_runner,
+            &alice,
+            open_hub_component,
+            bob_trader_component
+        ),
+        Some(bob.account)
+    );
+    assert_eq!(
+        get_account_for_trader(&mut test_runner, &alice, open_hub_component, carol.account),
+        None
+    );
+
+    assert_eq!(
+        get_trader_for_key(
+            &mut test_runner,
+            &alice,
+            open_hub_component,
+            alice_key_local
+        ),
+        Some(alice_trader_component)
+    );
+    assert_eq!(
+        get_trader_for_key(&mut test_runner, &alice, open_hub_component, bob_key_local),
+        Some(bob_trader_component)
+    );
+    assert_eq!(
+        get_trader_for_key(
+            &mut test_runner,
+            &alice,
+            open_hub_component,
+            NonFungibleLocalId::integer(7)
+        ),
+        None
+    );
+}
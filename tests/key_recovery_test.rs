@@ -0,0 +1,156 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn recovered_key_replaces_old_key() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (old_key_resource, old_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let (new_key_resource, new_key_local) =
+        recover_trader_key(&mut test_runner, &buyer, open_hub_component);
+
+    assert_eq!(
+        get_trader_for_key(
+            &mut test_runner,
+            &buyer,
+            open_hub_component,
+            old_key_local.clone()
+        ),
+        None
+    );
+    assert_eq!(
+        get_trader_for_key(
+            &mut test_runner,
+            &buyer,
+            open_hub_component,
+            new_key_local.clone()
+        ),
+        Some(buyer_trader_component)
+    );
+
+    // The new key controls the buyer's trader account.
+    let buyer_nft_address = create_nft_resource(&mut test_runner, &buyer, 0, 1, None);
+
+    list(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        new_key_resource,
+        new_key_local.clone(),
+        buyer_nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![],
+    );
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![],
+    );
+
+    add_private_buyer(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nfgid.clone(),
+        NonFungibleGlobalId::new(old_key_resource, old_key_local.clone()),
+    );
+
+    // A private deal targeted at the old key can't be taken with it once it has been replaced.
+    purchase_listing_with_badge(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(100),
+        old_key_resource,
+        old_key_local.clone(),
+    )
+    .expect_commit_failure();
+
+    // Nor can a swap reserved for the old key be accepted with it.
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        create_global_id(nft_address, 1),
+        vec![SwapAsset::Fungible(XRD, dec!(50))],
+        None,
+        Some(NonFungibleGlobalId::new(
+            old_key_resource,
+            old_key_local.clone(),
+        )),
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        swap_id,
+        dec!(50),
+        old_key_resource,
+        old_key_local,
+    )
+    .expect_commit_failure();
+
+    // Targeting the new key restores the deal.
+    add_private_buyer(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+        NonFungibleGlobalId::new(new_key_resource, new_key_local.clone()),
+    );
+
+    purchase_listing_with_badge(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        nfgid,
+        dec!(100),
+        new_key_resource,
+        new_key_local,
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, buyer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
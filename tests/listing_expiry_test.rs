@@ -0,0 +1,97 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn expired_listing_is_rejected_and_purged() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    let expiry = current_time(&mut test_runner).add_seconds(60).unwrap();
+
+    set_listing_expiry(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+        Some(expiry),
+    );
+
+    // A listing can't be purged before it expires.
+    purge_expired(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        vec![nfgid.clone()],
+    )
+    .expect_commit_failure();
+
+    advance_time(&mut test_runner, 120);
+
+    try_purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(100),
+        None,
+    )
+    .expect_commit_failure();
+
+    // Anyone can purge an expired listing, which returns the NFT to the seller.
+    purge_expired(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        vec![nfgid.clone()],
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, seller.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+
+    assert!(!is_listed(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        nfgid
+    ));
+}
@@ -0,0 +1,135 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn listing_index_pages_and_reorders() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (_marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 4, None);
+
+    for number in 0..4 {
+        list(
+            &mut test_runner,
+            &seller,
+            seller_trader_component,
+            seller_key_resource,
+            seller_key_local.clone(),
+            nft_address,
+            NonFungibleLocalId::integer(number),
+            dec!(100),
+            None,
+            vec![marketplace_key],
+        );
+    }
+
+    assert_eq!(
+        get_listings(&mut test_runner, &seller, seller_trader_component, 0, 10),
+        (0..4)
+            .map(|number| create_global_id(nft_address, number))
+            .collect::<Vec<_>>()
+    );
+
+    // Removing a listing from the middle moves the last listing into its slot.
+    cancel_listing(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        create_global_id(nft_address, 1),
+    );
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        3
+    );
+    assert_eq!(
+        get_listings(&mut test_runner, &seller, seller_trader_component, 0, 10),
+        vec![
+            create_global_id(nft_address, 0),
+            create_global_id(nft_address, 3),
+            create_global_id(nft_address, 2),
+        ]
+    );
+
+    // A relisted NFT goes to the end of the index and only appears once.
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(1),
+        dec!(50),
+        None,
+        vec![marketplace_key],
+    );
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        4
+    );
+    assert_eq!(
+        get_listings(&mut test_runner, &seller, seller_trader_component, 0, 10),
+        vec![
+            create_global_id(nft_address, 0),
+            create_global_id(nft_address, 3),
+            create_global_id(nft_address, 2),
+            create_global_id(nft_address, 1),
+        ]
+    );
+
+    // An NFT that is already listed is held in escrow, so it can't be listed a second time.
+    try_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(1),
+        dec!(50),
+        None,
+        vec![marketplace_key],
+        None,
+    )
+    .expect_commit_failure();
+
+    assert_eq!(
+        listing_count(&mut test_runner, &seller, seller_trader_component),
+        4
+    );
+
+    // Pages are cut short at the end of the index and are empty past it.
+    assert_eq!(
+        get_listings(&mut test_runner, &seller, seller_trader_component, 1, 2),
+        vec![
+            create_global_id(nft_address, 3),
+            create_global_id(nft_address, 2),
+        ]
+    );
+    assert_eq!(
+        get_listings(&mut test_runner, &seller, seller_trader_component, 3, 10),
+        vec![create_global_id(nft_address, 1)]
+    );
+    assert!(get_listings(&mut test_runner, &seller, seller_trader_component, 4, 10).is_empty());
+    assert!(get_listings(&mut test_runner, &seller, seller_trader_component, 10, 5).is_empty());
+}
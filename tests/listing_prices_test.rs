@@ -0,0 +1,193 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn listing_is_purchased_in_alternative_currency() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, buyer.account);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    set_alternative_price(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nfgid.clone(),
+        stablecoin,
+        dec!(0),
+    )
+    .expect_commit_failure();
+
+    set_alternative_price(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        nfgid,
+        dec!(5),
+        Some(stablecoin),
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(seller.account, stablecoin),
+        dec!(5)
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, buyer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
+
+#[test]
+fn change_price_updates_the_listing_price() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    change_price(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+        dec!(80),
+    );
+
+    let buyer_balance = test_runner.get_component_balance(buyer.account, XRD);
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        nfgid,
+        dec!(80),
+        None,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        buyer_balance - dec!(80)
+    );
+}
+
+#[test]
+fn dutch_listing_rejects_alternative_prices() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, seller.account);
+
+    let now = current_time(&mut test_runner);
+
+    dutch_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        DutchAuction {
+            start_price: dec!(100),
+            floor_price: dec!(50),
+            start_time: now,
+            end_time: now.add_seconds(1_000).unwrap(),
+            decay: PriceDecay::Linear,
+        },
+        vec![],
+        None,
+    );
+
+    set_alternative_price(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        create_global_id(nft_address, 0),
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_failure();
+}
@@ -0,0 +1,124 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn admin_withdraws_collected_fees() {
+    let (mut test_runner, operator, package) = setup_for_test();
+
+    let seller = make_user(&mut test_runner, Some("Bob"));
+    let buyer = make_user(&mut test_runner, Some("Carol"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &operator, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &operator, package, dec!(0.02));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+    );
+
+    purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(2)
+    );
+
+    let balance_before = test_runner.get_component_balance(operator.account, XRD);
+
+    withdraw_fees(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        Some(admin_key),
+        XRD,
+        dec!(1.5),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(operator.account, XRD) - balance_before,
+        dec!(1.5)
+    );
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(0.5)
+    );
+
+    withdraw_all_fees(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        Some(admin_key),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &operator, marketplace_component, XRD),
+        dec!(0)
+    );
+}
+
+#[test]
+fn fee_withdrawal_requires_admin_key() {
+    let (mut test_runner, operator, package) = setup_for_test();
+
+    let outsider = make_user(&mut test_runner, Some("Mallory"));
+
+    let (marketplace_component, _marketplace_key, admin_key) =
+        create_marketplace_with_admin(&mut test_runner, &operator, package, dec!(0.02));
+
+    withdraw_fees(
+        &mut test_runner,
+        &outsider,
+        marketplace_component,
+        None,
+        XRD,
+        dec!(0),
+    )
+    .expect_auth_failure();
+
+    withdraw_all_fees(&mut test_runner, &outsider, marketplace_component, None)
+        .expect_auth_failure();
+
+    withdraw_all_fees(
+        &mut test_runner,
+        &operator,
+        marketplace_component,
+        Some(admin_key),
+    )
+    .expect_commit_success();
+}
@@ -0,0 +1,425 @@
+use scrypto::prelude::*;
+use scrypto_test::prelude::*;
+
+use crate::common::*;
+
+/// Creates a GenericMarketplace and returns its component, listing key and admin key addresses.
+pub fn create_marketplace_with_admin(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    package: PackageAddress,
+    fee: Decimal,
+) -> (ComponentAddress, ResourceAddress, ResourceAddress) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_function(
+            package,
+            "GenericMarketplace",
+            "start_marketplace",
+            manifest_args!(fee, dec!(0)),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    let commit = receipt.expect_commit_success();
+    let component = commit.new_component_addresses()[0];
+    let new_resources = commit.new_resource_addresses().clone();
+
+    let marketplace_key = marketplace_key_address(test_runner, user, component);
+
+    let admin_key = new_resources
+        .into_iter()
+        .find(|resource| *resource != marketplace_key)
+        .expect("No admin key was created");
+
+    (component, marketplace_key, admin_key)
+}
+
+pub fn marketplace_key_address(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+) -> ResourceAddress {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            marketplace_component,
+            "get_marketplace_key_address",
+            manifest_args!(),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn fee_balance(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    currency: ResourceAddress,
+) -> Decimal {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            marketplace_component,
+            "fee_balance",
+            manifest_args!(currency),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+/// Withdraws marketplace fees, presenting the admin key when one is given. The receipt is returned
+/// so that tests can check both the permitted and the rejected case.
+pub fn withdraw_fees(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: Option<ResourceAddress>,
+    currency: ResourceAddress,
+    amount: Decimal,
+) -> TransactionReceipt {
+    let mut builder = ManifestBuilder::new().lock_fee_from_faucet();
+
+    if let Some(admin_key) = admin_key {
+        builder = builder.create_proof_from_account_of_amount(user.account, admin_key, dec!(1));
+    }
+
+    let manifest = builder
+        .call_method(
+            marketplace_component,
+            "withdraw_fees",
+            manifest_args!(currency, amount),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn withdraw_all_fees(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: Option<ResourceAddress>,
+) -> TransactionReceipt {
+    let mut builder = ManifestBuilder::new().lock_fee_from_faucet();
+
+    if let Some(admin_key) = admin_key {
+        builder = builder.create_proof_from_account_of_amount(user.account, admin_key, dec!(1));
+    }
+
+    let manifest = builder
+        .call_method(marketplace_component, "withdraw_all_fees", manifest_args!())
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Purchases a bundle through a marketplace and returns the receipt so tests can check purchases that should be rejected.
+pub fn purchase_bundle(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    trader_component: ComponentAddress,
+    bundle_id: u64,
+    payment: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                marketplace_component,
+                "purchase_bundle",
+                manifest_args!(
+                    bundle_id,
+                    lookup.bucket("payment"),
+                    trader_component,
+                    user.account,
+                ),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Sweeps listings through the marketplace with a single XRD payment. Each listing is the trader component, the NFT
+/// and the highest price the buyer accepts for it.
+pub fn sweep_listings(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    listings: Vec<(ComponentAddress, NonFungibleGlobalId, Decimal)>,
+    payment: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                marketplace_component,
+                "sweep_listings",
+                manifest_args!(listings, lookup.bucket("payment"), user.account),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn set_dex_router(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: ResourceAddress,
+    dex_router: Option<ComponentAddress>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_amount(user.account, admin_key, dec!(1))
+        .call_method(
+            marketplace_component,
+            "set_dex_router",
+            manifest_args!(dex_router),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Purchases a listing through the marketplace's DEX router, paying in another currency. A royal listing is purchased
+/// with purchase_royal_listing_with_swap and deposited to the user's account. The receipt is returned so tests can
+/// check slippage.
+pub fn purchase_with_swap(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    royal: bool,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+    currency: ResourceAddress,
+    payment: Decimal,
+    max_input: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(currency, payment))
+        .take_all_from_worktop(currency, "payment")
+        .with_name_lookup(|builder, lookup| {
+            if royal {
+                builder.call_method(
+                    marketplace_component,
+                    "purchase_royal_listing_with_swap",
+                    manifest_args!(
+                        nfgid,
+                        lookup.bucket("payment"),
+                        max_input,
+                        trader_component,
+                        user.account,
+                    ),
+                )
+            } else {
+                builder.call_method(
+                    marketplace_component,
+                    "purchase_listing_with_swap",
+                    manifest_args!(nfgid, lookup.bucket("payment"), max_input, trader_component),
+                )
+            }
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Sets a collection fee with the marketplace admin key. The receipt is returned so tests can check rejected rates.
+pub fn set_collection_fee(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: ResourceAddress,
+    collection: ResourceAddress,
+    fee_rate: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_amount(user.account, admin_key, dec!(1))
+        .call_method(
+            marketplace_component,
+            "set_collection_fee",
+            manifest_args!(collection, fee_rate),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn set_volume_tiers(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: ResourceAddress,
+    volume_tiers: Vec<(u64, Decimal)>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_amount(user.account, admin_key, dec!(1))
+        .call_method(
+            marketplace_component,
+            "set_volume_tiers",
+            manifest_args!(volume_tiers),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn add_promotion(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    admin_key: ResourceAddress,
+    start: Instant,
+    end: Instant,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_proof_from_account_of_amount(user.account, admin_key, dec!(1))
+        .call_method(
+            marketplace_component,
+            "add_promotion",
+            manifest_args!(start, end),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Purchases a listing at the fee tier of a buyer account. The receipt is returned so tests can check that only the
+/// owner of the account can claim its tier.
+pub fn purchase_listing_as_buyer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+    payment: Decimal,
+    account: ComponentAddress,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                marketplace_component,
+                "purchase_listing_as_buyer",
+                manifest_args!(nfgid, lookup.bucket("payment"), trader_component, account),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
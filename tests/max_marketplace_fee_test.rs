@@ -0,0 +1,210 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn listing_rejects_marketplace_fee_above_its_cap() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.05));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    try_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+        Some(dec!(0.02)),
+    )
+    .expect_commit_success();
+
+    // The marketplace charges 5% but the seller only accepts up to 2%.
+    try_purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    )
+    .expect_commit_failure();
+
+    set_listing_max_marketplace_fee(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        create_global_id(nft_address, 0),
+        Some(dec!(0.05)),
+    );
+
+    try_purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    )
+    .expect_commit_success();
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+}
+
+#[test]
+fn listing_cap_must_be_a_valid_rate() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (_marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.05));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    try_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![marketplace_key],
+        Some(dec!(1.5)),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn dutch_listing_rejects_marketplace_fee_above_its_cap() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.05));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let now = current_time(&mut test_runner);
+
+    dutch_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        DutchAuction {
+            start_price: dec!(100),
+            floor_price: dec!(50),
+            start_time: now,
+            end_time: now.add_seconds(1_000).unwrap(),
+            decay: PriceDecay::Linear,
+        },
+        vec![marketplace_key],
+        Some(dec!(0.02)),
+    );
+
+    // The cap set when Dutch listing is held to like any other listing's.
+    try_purchase_listing(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        create_global_id(nft_address, 0),
+        dec!(100),
+        None,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn bundle_rejects_marketplace_fee_above_its_cap() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.05));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    let bundle_id: u64 = try_bundle_list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nft_address,
+        vec![
+            NonFungibleLocalId::integer(0),
+            NonFungibleLocalId::integer(1),
+        ],
+        dec!(100),
+        vec![marketplace_key],
+        Some(dec!(0.02)),
+    )
+    .expect_commit(true)
+    .output(4);
+
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        seller_trader_component,
+        bundle_id,
+        dec!(100),
+    )
+    .expect_commit_failure();
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.is_empty());
+}
@@ -0,0 +1,302 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use scenario_manifests::*;
+
+#[test]
+fn mints_at_the_price_of_each_currency() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let minter = make_user(&mut test_runner, Some("Bob"));
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, minter.account);
+
+    let (royalty_nft_component, creator_key, minting_venue) =
+        create_mint_collection(&mut test_runner, &creator, &minter, package);
+
+    set_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    let xrd_before = test_runner.get_component_balance(minter.account, XRD);
+
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        XRD,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(minter.account, XRD),
+        xrd_before - dec!(100)
+    );
+
+    // Paying more than the price returns the change.
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        stablecoin,
+        dec!(7),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(minter.account, stablecoin),
+        dec!(995)
+    );
+
+    let nft_address = nft_address(&mut test_runner, &creator, royalty_nft_component);
+
+    let minted = get_component_nflids(&mut test_runner, minter.account, nft_address);
+
+    assert_eq!(minted.len(), 2);
+}
+
+#[test]
+fn rejects_currencies_not_in_the_price_table() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let minter = make_user(&mut test_runner, Some("Bob"));
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, minter.account);
+
+    let (royalty_nft_component, _creator_key, minting_venue) =
+        create_mint_collection(&mut test_runner, &creator, &minter, package);
+
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        stablecoin,
+        dec!(100),
+    )
+    .expect_commit_failure();
+
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        XRD,
+        dec!(50),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn last_mint_currency_can_not_be_removed() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let minter = make_user(&mut test_runner, Some("Bob"));
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, minter.account);
+
+    let (royalty_nft_component, creator_key, minting_venue) =
+        create_mint_collection(&mut test_runner, &creator, &minter, package);
+
+    remove_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        XRD,
+    )
+    .expect_commit_failure();
+
+    set_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    remove_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        XRD,
+    )
+    .expect_commit_success();
+
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        XRD,
+        dec!(100),
+    )
+    .expect_commit_failure();
+
+    remove_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        stablecoin,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn creator_withdraws_mint_proceeds_per_currency() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let minter = make_user(&mut test_runner, Some("Bob"));
+
+    let stablecoin = test_runner.create_fungible_resource(dec!(1000), 18, minter.account);
+
+    let (royalty_nft_component, creator_key, minting_venue) =
+        create_mint_collection(&mut test_runner, &creator, &minter, package);
+
+    set_mint_price(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+    );
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+    );
+
+    try_mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+        stablecoin,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    let xrd_before = test_runner.get_component_balance(creator.account, XRD);
+
+    withdraw_mint_proceeds(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        XRD,
+        dec!(50),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(creator.account, XRD),
+        xrd_before + dec!(50)
+    );
+
+    // Only 150 XRD of proceeds are left.
+    withdraw_mint_proceeds(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+        XRD,
+        dec!(200),
+    )
+    .expect_commit_failure();
+
+    withdraw_all_mint_proceeds(
+        &mut test_runner,
+        &creator,
+        royalty_nft_component,
+        creator_key,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(creator.account, XRD),
+        xrd_before + dec!(200)
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(creator.account, stablecoin),
+        dec!(5)
+    );
+}
+
+#[test]
+fn only_the_creator_can_manage_mint_prices_and_proceeds() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let minter = make_user(&mut test_runner, Some("Bob"));
+
+    let (royalty_nft_component, _creator_key, minting_venue) =
+        create_mint_collection(&mut test_runner, &creator, &minter, package);
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        minting_venue,
+    );
+
+    // The minter presents a badge of their own in place of the creator key.
+    let fake_key = test_runner.create_fungible_resource(dec!(1), 0, minter.account);
+
+    set_mint_price(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        fake_key,
+        XRD,
+        dec!(1),
+    )
+    .expect_commit_failure();
+
+    remove_mint_price(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        fake_key,
+        XRD,
+    )
+    .expect_commit_failure();
+
+    withdraw_mint_proceeds(
+        &mut test_runner,
+        &minter,
+        royalty_nft_component,
+        fake_key,
+        XRD,
+        dec!(100),
+    )
+    .expect_commit_failure();
+}
@@ -189,7 +189,7 @@ pub fn create_marketplace(
             package,
             "GenericMarketplace",
             "start_marketplace",
-            manifest_args!(fee),
+            manifest_args!(fee, dec!(0)),
         )
         .call_method(
             user.account,
@@ -573,3 +573,161 @@ pub fn withdraw_royalty_nft(
         panic!("TRANSACTION FAIL");
     }
 }
+
+pub fn get_trader_for_account(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    open_hub_component: ComponentAddress,
+    account: ComponentAddress,
+) -> Option<ComponentAddress> {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            open_hub_component,
+            "get_trader_for_account",
+            manifest_args!(account),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn get_account_for_trader(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    open_hub_component: ComponentAddress,
+    trader_component: ComponentAddress,
+) -> Option<ComponentAddress> {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            open_hub_component,
+            "get_account_for_trader",
+            manifest_args!(trader_component),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn get_trader_for_key(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    open_hub_component: ComponentAddress,
+    key_local: NonFungibleLocalId,
+) -> Option<ComponentAddress> {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            open_hub_component,
+            "get_trader_for_key",
+            manifest_args!(key_local),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn total_accounts(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    open_hub_component: ComponentAddress,
+) -> u64 {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(open_hub_component, "total_accounts", manifest_args!())
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+/// Recovers the trader key of the user's trader account, returning the new key.
+pub fn recover_trader_key(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    open_hub_component: ComponentAddress,
+) -> (ResourceAddress, NonFungibleLocalId) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            open_hub_component,
+            "recover_trader_key",
+            manifest_args!(user.account),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    let (trader_key, _): (NonFungibleGlobalId, Bucket) = receipt.expect_commit(true).output(1);
+
+    trader_key.into_parts()
+}
+
+/// Creates a DexPool paying out XRD for an input token at a fixed rate, funded with XRD from the user's account.
+pub fn create_dex_pool(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    package: PackageAddress,
+    input_resource: ResourceAddress,
+    rate: Decimal,
+    liquidity: Decimal,
+) -> ComponentAddress {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, liquidity))
+        .take_all_from_worktop(XRD, "liquidity")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_function(
+                package,
+                "DexPool",
+                "start_dex_pool",
+                manifest_args!(lookup.bucket("liquidity"), input_resource, rate),
+            )
+        })
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    receipt.expect_commit_success().new_component_addresses()[0]
+}
@@ -0,0 +1,312 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use scenario_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn make_and_accept_collection_offer() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (buyer_key_resource, buyer_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+
+    // The same 100 XRD backs an offer on a specific NFT and a collection offer.
+    let offer_id = make_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local,
+        dec!(100),
+        vec![
+            (
+                OfferTarget::Nft(NonFungibleGlobalId::new(
+                    nft_address,
+                    NonFungibleLocalId::integer(0),
+                )),
+                dec!(100),
+            ),
+            (OfferTarget::Collection(nft_address), dec!(80)),
+        ],
+    );
+
+    accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        nft_address,
+        NonFungibleLocalId::integer(1),
+    );
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(1)));
+}
+
+#[test]
+fn cancel_offer_returns_escrow() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (buyer_key_resource, buyer_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let balance_before = test_runner.get_component_balance(buyer.account, XRD);
+
+    let offer_id = make_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local.clone(),
+        dec!(100),
+        vec![(OfferTarget::Collection(nft_address), dec!(100))],
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        balance_before - dec!(100)
+    );
+
+    cancel_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local,
+        offer_id,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        balance_before
+    );
+
+    try_accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn offer_rejects_nft_outside_its_targets() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (buyer_key_resource, buyer_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 2, None);
+    let other_nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let offer_id = make_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local,
+        dec!(100),
+        vec![(
+            OfferTarget::Nft(NonFungibleGlobalId::new(
+                nft_address,
+                NonFungibleLocalId::integer(0),
+            )),
+            dec!(100),
+        )],
+    );
+
+    // A different NFT from the same collection doesn't match an offer on a specific NFT.
+    try_accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        nft_address,
+        NonFungibleLocalId::integer(1),
+    )
+    .expect_commit_failure();
+
+    try_accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        other_nft_address,
+        NonFungibleLocalId::integer(0),
+    )
+    .expect_commit_failure();
+
+    // The offer is still open for its actual target.
+    accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+    );
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+}
+
+#[test]
+fn expired_offer_cannot_be_accepted() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (buyer_key_resource, buyer_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+
+    let offer_id = make_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local.clone(),
+        dec!(100),
+        vec![(OfferTarget::Collection(nft_address), dec!(100))],
+    );
+
+    let expiry = current_time(&mut test_runner).add_seconds(60).unwrap();
+
+    set_offer_expiry(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local.clone(),
+        offer_id,
+        Some(expiry),
+    );
+
+    advance_time(&mut test_runner, 120);
+
+    try_accept_offer(
+        &mut test_runner,
+        &seller,
+        buyer_trader_component,
+        offer_id,
+        nft_address,
+        NonFungibleLocalId::integer(0),
+    )
+    .expect_commit_failure();
+
+    // The escrow of an expired offer can still be reclaimed.
+    cancel_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local,
+        offer_id,
+    );
+}
+
+#[test]
+fn offer_maker_is_the_buyer_of_a_royalty_nft() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    let (buyer_key_resource, buyer_key_local, buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, collection.open_hub_component);
+
+    let offer_id = make_offer(
+        &mut test_runner,
+        &buyer,
+        buyer_trader_component,
+        buyer_key_resource,
+        buyer_key_local,
+        dec!(100),
+        vec![(
+            OfferTarget::Nft(create_global_id(collection.nft_address, 0)),
+            dec!(100),
+        )],
+    );
+
+    deny_all_buyers(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    try_accept_offer(
+        &mut test_runner,
+        &creator,
+        buyer_trader_component,
+        offer_id,
+        collection.nft_address,
+        NonFungibleLocalId::integer(0),
+    )
+    .expect_commit_failure();
+
+    // The royalty component sees the offer maker's trader key as the buyer.
+    add_permissioned_buyer(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        buyer_key_resource,
+    );
+
+    accept_offer(
+        &mut test_runner,
+        &creator,
+        buyer_trader_component,
+        offer_id,
+        collection.nft_address,
+        NonFungibleLocalId::integer(0),
+    );
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, collection.nft_address);
+
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(10)
+    );
+}
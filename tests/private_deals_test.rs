@@ -0,0 +1,84 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn private_deal_is_only_purchasable_by_targeted_buyer() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+    let outsider = make_user(&mut test_runner, Some("Mallory"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (buyer_key_resource, buyer_key_local, _buyer_trader_component) =
+        create_open_trader(&mut test_runner, &buyer, open_hub_component);
+
+    let (outsider_key_resource, outsider_key_local, _outsider_trader_component) =
+        create_open_trader(&mut test_runner, &outsider, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 1, None);
+    let nfgid = create_global_id(nft_address, 0);
+
+    list(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local.clone(),
+        nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![],
+    );
+
+    add_private_buyer(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        nfgid.clone(),
+        NonFungibleGlobalId::new(buyer_key_resource, buyer_key_local.clone()),
+    );
+
+    // Another holder of a trader key from the same hub isn't the targeted buyer.
+    purchase_listing_with_badge(
+        &mut test_runner,
+        &outsider,
+        seller_trader_component,
+        nfgid.clone(),
+        dec!(100),
+        outsider_key_resource,
+        outsider_key_local,
+    )
+    .expect_commit_failure();
+
+    purchase_listing_with_badge(
+        &mut test_runner,
+        &buyer,
+        seller_trader_component,
+        nfgid,
+        dec!(100),
+        buyer_key_resource,
+        buyer_key_local,
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, buyer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
@@ -0,0 +1,184 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use scenario_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn reactive_data_tracks_listings_and_sales() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    assert!(!listing_hook(&mut test_runner, collection.nft_address));
+
+    enable_reactive_data(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    assert!(listing_hook(&mut test_runner, collection.nft_address));
+
+    list_royalty_nft(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        collection.nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        None,
+        vec![collection.marketplace_key],
+    );
+
+    assert!(royal_nft_data(&mut test_runner, collection.nft_address, 0).listed);
+
+    purchase_royalty_nft(
+        &mut test_runner,
+        &buyer,
+        collection.marketplace_component,
+        collection.trader_component,
+        create_global_id(collection.nft_address, 0),
+        dec!(100),
+        None,
+    );
+
+    let nft_data = royal_nft_data(&mut test_runner, collection.nft_address, 0);
+
+    assert_eq!(nft_data.sale_count, 1);
+    assert_eq!(nft_data.last_sale, Some((dec!(100), XRD)));
+    assert!(nft_data.last_sale_time.is_some());
+    assert!(!nft_data.listed);
+}
+
+#[test]
+fn reactive_data_tracks_auctions_and_bundles() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 3);
+
+    enable_reactive_data(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    let now = current_time(&mut test_runner);
+
+    auction_list(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        collection.nft_address,
+        NonFungibleLocalId::integer(0),
+        dec!(100),
+        dec!(1),
+        now,
+        now.add_seconds(3600).unwrap(),
+        vec![collection.marketplace_key],
+    );
+
+    assert!(royal_nft_data(&mut test_runner, collection.nft_address, 0).listed);
+
+    cancel_auction(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        create_global_id(collection.nft_address, 0),
+    )
+    .expect_commit_success();
+
+    // The listing hook isn't called when an NFT is returned to its owner, so a cancelled auction stays marked as listed.
+    assert!(royal_nft_data(&mut test_runner, collection.nft_address, 0).listed);
+
+    let bundle_id = bundle_list(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        collection.nft_address,
+        vec![
+            NonFungibleLocalId::integer(1),
+            NonFungibleLocalId::integer(2),
+        ],
+        dec!(100),
+        vec![collection.marketplace_key],
+    );
+
+    assert!(royal_nft_data(&mut test_runner, collection.nft_address, 1).listed);
+    assert!(royal_nft_data(&mut test_runner, collection.nft_address, 2).listed);
+
+    purchase_bundle(
+        &mut test_runner,
+        &buyer,
+        collection.marketplace_component,
+        collection.trader_component,
+        bundle_id,
+        dec!(100),
+    )
+    .expect_commit_success();
+
+    // Each NFT records the portion of the bundle price it was sold for.
+    for number in 1..3 {
+        let nft_data = royal_nft_data(&mut test_runner, collection.nft_address, number);
+
+        assert_eq!(nft_data.sale_count, 1);
+        assert_eq!(nft_data.last_sale, Some((dec!(50), XRD)));
+        assert!(!nft_data.listed);
+    }
+}
+
+#[test]
+fn listing_hook_is_not_called_without_reactive_data() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    enable_reactive_data(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    disable_reactive_data(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    assert!(!listing_hook(&mut test_runner, collection.nft_address));
+
+    let royalty = sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0);
+
+    assert_eq!(royalty, dec!(10));
+
+    let nft_data = royal_nft_data(&mut test_runner, collection.nft_address, 0);
+
+    assert_eq!(nft_data.sale_count, 0);
+    assert_eq!(nft_data.last_sale, None);
+    assert!(!nft_data.listed);
+}
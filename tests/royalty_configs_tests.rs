@@ -74,9 +74,23 @@ fn royalty_config_changes() {
         creator_key,
         new_fee,
     );
-    enable_mint_reveal(&mut test_runner, &user, royalty_nft_component, creator_key);
 
-    mint_royalty_nft(&mut test_runner, &user, royalty_nft_component);
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, user.account);
+
+    enable_mint_reveal(
+        &mut test_runner,
+        &user,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &user,
+        royalty_nft_component,
+        minting_venue,
+    );
 
     let nft_address = nft_address(&mut test_runner, &user, royalty_nft_component);
 
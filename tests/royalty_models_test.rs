@@ -0,0 +1,180 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use scenario_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn flat_royalty_replaces_the_percentage() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(3),
+    )
+    .expect_commit_success();
+
+    set_royalty_model(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        RoyaltyModel::Flat,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0),
+        dec!(3)
+    );
+}
+
+#[test]
+fn hybrid_models_pick_the_greater_or_lesser_royalty() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 2);
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(20),
+    )
+    .expect_commit_success();
+
+    set_royalty_model(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        RoyaltyModel::GreaterOf,
+    )
+    .expect_commit_success();
+
+    // 10% of 100 XRD is 10 XRD, below the flat 20 XRD.
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0),
+        dec!(20)
+    );
+
+    set_royalty_model(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        RoyaltyModel::LesserOf,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 1),
+        dec!(10)
+    );
+}
+
+#[test]
+fn royalty_is_capped_at_the_maximum_royalty_percent() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(80),
+    )
+    .expect_commit_success();
+
+    set_royalty_model(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        RoyaltyModel::Flat,
+    )
+    .expect_commit_success();
+
+    // The maximum royalty is 50% of the 100 XRD sale.
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0),
+        dec!(50)
+    );
+}
+
+#[test]
+fn locked_configuration_only_allows_lower_flat_royalties() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 0);
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(5),
+    )
+    .expect_commit_success();
+
+    lock_royalty_configuration(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    set_royalty_model(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        RoyaltyModel::Flat,
+    )
+    .expect_commit_failure();
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(6),
+    )
+    .expect_commit_failure();
+
+    set_flat_royalty_amount(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(4),
+    )
+    .expect_commit_success();
+}
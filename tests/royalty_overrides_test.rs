@@ -0,0 +1,302 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use scenario_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn royalty_override_applies_to_one_nft() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 2);
+
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.2),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0),
+        dec!(20)
+    );
+
+    // NFTs without an override pay the collection's 10% royalty.
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 1),
+        dec!(10)
+    );
+}
+
+#[test]
+fn royalty_override_is_limited_by_the_configuration() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    // The maximum royalty percentage is 50%.
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.6),
+    )
+    .expect_commit_failure();
+
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(-0.05),
+    )
+    .expect_commit_failure();
+
+    lock_royalty_configuration(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.2),
+    )
+    .expect_commit_failure();
+
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.05),
+    )
+    .expect_commit_success();
+}
+
+#[test]
+fn royalty_override_scales_the_swap_royalty() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(
+            &mut test_runner,
+            &counterparty,
+            collection.open_hub_component,
+        );
+
+    set_swap_royalty(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(5),
+    );
+
+    // Double the collection's royalty percentage doubles the swap royalty.
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.2),
+    )
+    .expect_commit_success();
+
+    let royalties_before = royalty_balance(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        XRD,
+    );
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        create_global_id(collection.nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        Some(dec!(10)),
+        None,
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        collection.trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        royalties_before + dec!(10)
+    );
+}
+
+#[test]
+fn royalty_override_applies_to_swaps_without_a_collection_royalty() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(
+            &mut test_runner,
+            &counterparty,
+            collection.open_hub_component,
+        );
+
+    change_royalty_percentage_fee(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        dec!(0),
+    );
+
+    set_swap_royalty(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(5),
+    );
+
+    // There's no collection percentage to scale against, so the NFT pays the swap royalty unchanged.
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.2),
+    )
+    .expect_commit_success();
+
+    let royalties_before = royalty_balance(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        XRD,
+    );
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        create_global_id(collection.nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        Some(dec!(5)),
+        None,
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        collection.trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        royalties_before + dec!(5)
+    );
+}
+
+#[test]
+fn original_royalty_interface_uses_the_collection_royalty() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    set_royalty_override(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        NonFungibleLocalId::integer(0),
+        dec!(0.2),
+    )
+    .expect_commit_success();
+
+    let royalties_before = royalty_balance(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        XRD,
+    );
+
+    // Callers of the original interface don't say which NFT is sold, so no override can apply.
+    pay_royalty(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.nft_address,
+        dec!(100),
+        collection.marketplace_key,
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        royalties_before + dec!(10)
+    );
+}
@@ -0,0 +1,167 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use scenario_manifests::*;
+
+#[test]
+fn split_shares_must_add_up_to_10000_basis_points() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let team_member = make_user(&mut test_runner, Some("Dave"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 0);
+
+    set_royalty_splits(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        vec![
+            (RoyaltyPayee::Account(creator.account), 5000),
+            (RoyaltyPayee::Account(team_member.account), 4000),
+        ],
+    )
+    .expect_commit_failure();
+
+    set_royalty_splits(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        vec![
+            (RoyaltyPayee::Account(creator.account), 10000),
+            (RoyaltyPayee::Account(team_member.account), 0),
+        ],
+    )
+    .expect_commit_failure();
+
+    set_royalty_splits(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        vec![
+            (RoyaltyPayee::Account(creator.account), 6000),
+            (RoyaltyPayee::Account(team_member.account), 4000),
+        ],
+    )
+    .expect_commit_success();
+}
+
+#[test]
+fn royalties_are_split_between_account_and_badge_payees() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+    let account_payee = make_user(&mut test_runner, Some("Dave"));
+    let badge_payee = make_user(&mut test_runner, Some("Erin"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    let payee_badge_address = create_nft_resource(&mut test_runner, &badge_payee, 0, 1, None);
+    let payee_badge = create_global_id(payee_badge_address, 0);
+
+    set_royalty_splits(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        vec![
+            (RoyaltyPayee::Account(account_payee.account), 6000),
+            (RoyaltyPayee::Badge(payee_badge.clone()), 4000),
+        ],
+    )
+    .expect_commit_success();
+
+    let account_payee_balance = test_runner.get_component_balance(account_payee.account, XRD);
+
+    // 10% of the 100 XRD sale is split 60/40.
+    assert_eq!(
+        sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0),
+        dec!(0)
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(account_payee.account, XRD),
+        account_payee_balance + dec!(6)
+    );
+
+    assert_eq!(
+        split_royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            payee_badge.clone(),
+            XRD
+        ),
+        dec!(4)
+    );
+
+    // Only the badge holder can claim its share.
+    claim_split_royalties(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        payee_badge.clone(),
+    )
+    .expect_commit_failure();
+
+    let badge_payee_balance = test_runner.get_component_balance(badge_payee.account, XRD);
+
+    claim_split_royalties(
+        &mut test_runner,
+        &badge_payee,
+        collection.royalty_nft_component,
+        payee_badge.clone(),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(badge_payee.account, XRD),
+        badge_payee_balance + dec!(4)
+    );
+
+    // Nothing is left to claim.
+    claim_split_royalties(
+        &mut test_runner,
+        &badge_payee,
+        collection.royalty_nft_component,
+        payee_badge,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn locked_configuration_locks_the_split() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let team_member = make_user(&mut test_runner, Some("Dave"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 0);
+
+    lock_royalty_configuration(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    set_royalty_splits(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        vec![
+            (RoyaltyPayee::Account(creator.account), 5000),
+            (RoyaltyPayee::Account(team_member.account), 5000),
+        ],
+    )
+    .expect_commit_failure();
+}
@@ -0,0 +1,168 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use scenario_manifests::*;
+
+#[test]
+fn creator_withdraws_all_royalties() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0);
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(10)
+    );
+
+    let balance_before = test_runner.get_component_balance(creator.account, XRD);
+
+    withdraw_all_royalties(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+    );
+
+    assert_eq!(
+        test_runner.get_component_balance(creator.account, XRD),
+        balance_before + dec!(10)
+    );
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(0)
+    );
+}
+
+#[test]
+fn creator_withdraws_part_of_the_royalties() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 1);
+
+    sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0);
+
+    let balance_before = test_runner.get_component_balance(creator.account, XRD);
+
+    withdraw_royalties(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(4),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(creator.account, XRD),
+        balance_before + dec!(4)
+    );
+
+    // Only 6 XRD of royalties are left.
+    withdraw_royalties(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        XRD,
+        dec!(7),
+    )
+    .expect_commit_failure();
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(6)
+    );
+}
+
+#[test]
+fn royalties_are_forwarded_to_the_payout_account() {
+    let (mut test_runner, creator, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let payout = make_user(&mut test_runner, Some("Carol"));
+
+    let collection = create_royal_collection(&mut test_runner, &creator, package, 2);
+
+    set_royalty_payout_account(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        Some(payout.account),
+    );
+
+    let payout_balance = test_runner.get_component_balance(payout.account, XRD);
+
+    sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 0);
+
+    assert_eq!(
+        test_runner.get_component_balance(payout.account, XRD),
+        payout_balance + dec!(10)
+    );
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(0)
+    );
+
+    // Once forwarding is turned off, royalties are held in the royalty vault again.
+    set_royalty_payout_account(
+        &mut test_runner,
+        &creator,
+        collection.royalty_nft_component,
+        collection.creator_key,
+        None,
+    );
+
+    sell_royal_nft(&mut test_runner, &creator, &buyer, &collection, 1);
+
+    assert_eq!(
+        test_runner.get_component_balance(payout.account, XRD),
+        payout_balance + dec!(10)
+    );
+
+    assert_eq!(
+        royalty_balance(
+            &mut test_runner,
+            &creator,
+            collection.royalty_nft_component,
+            XRD
+        ),
+        dec!(10)
+    );
+}
@@ -0,0 +1,152 @@
+use scrypto::prelude::*;
+use scrypto_test::prelude::*;
+
+use crate::common::*;
+use crate::creator_manifests::*;
+use crate::misc_manifests::*;
+use crate::trader_manifests::*;
+
+/// A royalty enforced collection (10% royalty, capped at 50%) with NFTs owned by the creator's trader account
+/// and a fee free marketplace to sell them through.
+pub struct RoyalCollection {
+    pub open_hub_component: ComponentAddress,
+    pub royalty_nft_component: ComponentAddress,
+    pub creator_key: ResourceAddress,
+    pub nft_address: ResourceAddress,
+    pub trader_component: ComponentAddress,
+    pub trader_key_resource: ResourceAddress,
+    pub trader_key_local: NonFungibleLocalId,
+    pub marketplace_component: ComponentAddress,
+    pub marketplace_key: ResourceAddress,
+}
+
+pub fn create_royal_collection(
+    test_runner: &mut DefaultLedgerSimulator,
+    creator: &User,
+    package: PackageAddress,
+    nft_count: u64,
+) -> RoyalCollection {
+    let open_hub_component = instantiate_open_hub(test_runner, creator, package);
+
+    let virtual_badge = fetch_virt_badge(test_runner, creator, open_hub_component);
+
+    let depositer_badge = fetch_depositer_badge(test_runner, creator, open_hub_component);
+
+    let (trader_key_resource, trader_key_local, trader_component) =
+        create_open_trader(test_runner, creator, open_hub_component);
+
+    create_event_listener(test_runner, creator, package, virtual_badge);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(test_runner, creator, package, dec!(0));
+
+    let mint_factory = create_mint_factory(test_runner, creator, package);
+
+    let (royalty_nft_component, creator_key) = create_royalty_nft(
+        test_runner,
+        creator,
+        mint_factory,
+        defaults_royalty_config(),
+        depositer_badge,
+    );
+
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, creator.account);
+
+    enable_mint_reveal(
+        test_runner,
+        creator,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    for _ in 0..nft_count {
+        mint_royalty_nft(test_runner, creator, royalty_nft_component, minting_venue);
+    }
+
+    let nft_address = nft_address(test_runner, creator, royalty_nft_component);
+
+    RoyalCollection {
+        open_hub_component,
+        royalty_nft_component,
+        creator_key,
+        nft_address,
+        trader_component,
+        trader_key_resource,
+        trader_key_local,
+        marketplace_component,
+        marketplace_key,
+    }
+}
+
+/// Lists and sells an NFT of the collection for 100 XRD, returning the royalty paid on the sale.
+pub fn sell_royal_nft(
+    test_runner: &mut DefaultLedgerSimulator,
+    creator: &User,
+    buyer: &User,
+    collection: &RoyalCollection,
+    number: u64,
+) -> Decimal {
+    let royalties_before =
+        royalty_balance(test_runner, creator, collection.royalty_nft_component, XRD);
+
+    list_royalty_nft(
+        test_runner,
+        creator,
+        collection.trader_component,
+        collection.trader_key_resource,
+        collection.trader_key_local.clone(),
+        collection.nft_address,
+        NonFungibleLocalId::integer(number),
+        dec!(100),
+        None,
+        vec![collection.marketplace_key],
+    );
+
+    purchase_royalty_nft(
+        test_runner,
+        buyer,
+        collection.marketplace_component,
+        collection.trader_component,
+        create_global_id(collection.nft_address, number),
+        dec!(100),
+        None,
+    );
+
+    royalty_balance(test_runner, creator, collection.royalty_nft_component, XRD) - royalties_before
+}
+
+/// A royalty enforced collection with preview minting enabled at 100 XRD, where the minter holds the minting venue badge.
+/// Returns the royalty NFT component, the creator key and the minting venue badge.
+pub fn create_mint_collection(
+    test_runner: &mut DefaultLedgerSimulator,
+    creator: &User,
+    minter: &User,
+    package: PackageAddress,
+) -> (ComponentAddress, ResourceAddress, ResourceAddress) {
+    let open_hub_component = instantiate_open_hub(test_runner, creator, package);
+
+    let depositer_badge = fetch_depositer_badge(test_runner, creator, open_hub_component);
+
+    let mint_factory = create_mint_factory(test_runner, creator, package);
+
+    let (royalty_nft_component, creator_key) = create_royalty_nft(
+        test_runner,
+        creator,
+        mint_factory,
+        defaults_royalty_config(),
+        depositer_badge,
+    );
+
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, minter.account);
+
+    enable_mint_reveal(
+        test_runner,
+        creator,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    (royalty_nft_component, creator_key, minting_venue)
+}
@@ -40,9 +40,22 @@ fn list_and_purchase_standard_nft() {
         depositer_badger.clone(),
     );
 
-    enable_mint_reveal(&mut test_runner, &user, royalty_nft_component, creator_key);
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, user.account);
 
-    mint_royalty_nft(&mut test_runner, &user, royalty_nft_component);
+    enable_mint_reveal(
+        &mut test_runner,
+        &user,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &user,
+        royalty_nft_component,
+        minting_venue,
+    );
 
     let nft_address = nft_address(&mut test_runner, &user, royalty_nft_component);
 
@@ -0,0 +1,299 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn swap_nft_for_tokens() {
+    let (mut test_runner, proposer, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &proposer, package);
+
+    let (proposer_key_resource, proposer_key_local, proposer_trader_component) =
+        create_open_trader(&mut test_runner, &proposer, open_hub_component);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(&mut test_runner, &counterparty, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &proposer, 0, 1, None);
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &proposer,
+        proposer_trader_component,
+        proposer_key_resource,
+        proposer_key_local,
+        create_global_id(nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        None,
+        None,
+    );
+
+    let counterparty_balance_before = test_runner.get_component_balance(counterparty.account, XRD);
+
+    // The counterparty must provide exactly the requested assets.
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(200),
+        counterparty_key_resource,
+        counterparty_key_local.clone(),
+    )
+    .expect_commit_failure();
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local.clone(),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(counterparty.account, XRD),
+        counterparty_balance_before - dec!(250)
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, counterparty.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+
+    // A swap can only be accepted once.
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn swap_reserved_for_counterparty_rejects_others() {
+    let (mut test_runner, proposer, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+    let outsider = make_user(&mut test_runner, Some("Mallory"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &proposer, package);
+
+    let (proposer_key_resource, proposer_key_local, proposer_trader_component) =
+        create_open_trader(&mut test_runner, &proposer, open_hub_component);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(&mut test_runner, &counterparty, open_hub_component);
+
+    let (outsider_key_resource, outsider_key_local, _outsider_trader_component) =
+        create_open_trader(&mut test_runner, &outsider, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &proposer, 0, 1, None);
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &proposer,
+        proposer_trader_component,
+        proposer_key_resource,
+        proposer_key_local,
+        create_global_id(nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        None,
+        Some(NonFungibleGlobalId::new(
+            counterparty_key_resource,
+            counterparty_key_local.clone(),
+        )),
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &outsider,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        outsider_key_resource,
+        outsider_key_local,
+    )
+    .expect_commit_failure();
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_success();
+
+    assert!(
+        get_component_nflids(&mut test_runner, counterparty.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
+
+#[test]
+fn cancelled_swap_returns_assets_and_cannot_be_accepted() {
+    let (mut test_runner, proposer, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &proposer, package);
+
+    let (proposer_key_resource, proposer_key_local, proposer_trader_component) =
+        create_open_trader(&mut test_runner, &proposer, open_hub_component);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(&mut test_runner, &counterparty, open_hub_component);
+
+    let nft_address = create_nft_resource(&mut test_runner, &proposer, 0, 1, None);
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &proposer,
+        proposer_trader_component,
+        proposer_key_resource,
+        proposer_key_local.clone(),
+        create_global_id(nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        None,
+        None,
+    );
+
+    cancel_swap(
+        &mut test_runner,
+        &proposer,
+        proposer_trader_component,
+        proposer_key_resource,
+        proposer_key_local,
+        swap_id,
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, proposer.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_failure();
+}
+
+#[test]
+fn swapping_royalty_nft_pays_swap_royalty() {
+    let (mut test_runner, proposer, package) = setup_for_test();
+
+    let counterparty = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &proposer, package);
+
+    let virtual_badge = fetch_virt_badge(&mut test_runner, &proposer, open_hub_component);
+
+    let depositer_badge = fetch_depositer_badge(&mut test_runner, &proposer, open_hub_component);
+
+    let (proposer_key_resource, proposer_key_local, proposer_trader_component) =
+        create_open_trader(&mut test_runner, &proposer, open_hub_component);
+
+    let (counterparty_key_resource, counterparty_key_local, _counterparty_trader_component) =
+        create_open_trader(&mut test_runner, &counterparty, open_hub_component);
+
+    create_event_listener(&mut test_runner, &proposer, package, virtual_badge);
+
+    let mint_factory = create_mint_factory(&mut test_runner, &proposer, package);
+
+    let (royalty_nft_component, creator_key) = create_royalty_nft(
+        &mut test_runner,
+        &proposer,
+        mint_factory,
+        defaults_royalty_config(),
+        depositer_badge,
+    );
+
+    let minting_venue = test_runner.create_fungible_resource(dec!(1), 0, proposer.account);
+
+    enable_mint_reveal(
+        &mut test_runner,
+        &proposer,
+        royalty_nft_component,
+        creator_key,
+        minting_venue,
+    );
+
+    mint_royalty_nft(
+        &mut test_runner,
+        &proposer,
+        royalty_nft_component,
+        minting_venue,
+    );
+
+    let nft_address = nft_address(&mut test_runner, &proposer, royalty_nft_component);
+
+    set_swap_royalty(
+        &mut test_runner,
+        &proposer,
+        royalty_nft_component,
+        creator_key,
+        XRD,
+        dec!(5),
+    );
+
+    let royalties_before = royalty_balance(&mut test_runner, &proposer, royalty_nft_component, XRD);
+
+    let swap_id = propose_swap(
+        &mut test_runner,
+        &proposer,
+        proposer_trader_component,
+        proposer_key_resource,
+        proposer_key_local,
+        create_global_id(nft_address, 0),
+        vec![SwapAsset::Fungible(XRD, dec!(250))],
+        Some(dec!(5)),
+        None,
+    );
+
+    accept_swap(
+        &mut test_runner,
+        &counterparty,
+        proposer_trader_component,
+        swap_id,
+        dec!(250),
+        counterparty_key_resource,
+        counterparty_key_local,
+    )
+    .expect_commit_success();
+
+    // The proposer's royalty deposit pays the swap royalty of the royalty NFT they gave away.
+    assert_eq!(
+        royalty_balance(&mut test_runner, &proposer, royalty_nft_component, XRD),
+        royalties_before + dec!(5)
+    );
+
+    assert!(
+        get_component_nflids(&mut test_runner, counterparty.account, nft_address)
+            .contains(&NonFungibleLocalId::integer(0))
+    );
+}
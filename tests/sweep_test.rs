@@ -0,0 +1,106 @@
+use scrypto_test::prelude::*;
+mod common;
+mod creator_manifests;
+mod marketplace_manifests;
+mod misc_manifests;
+mod scenario_manifests;
+mod trader_manifests;
+use common::*;
+use creator_manifests::*;
+use marketplace_manifests::*;
+use misc_manifests::*;
+use trader_manifests::*;
+
+#[test]
+fn sweep_skips_listings_it_cannot_buy() {
+    let (mut test_runner, seller, package) = setup_for_test();
+
+    let buyer = make_user(&mut test_runner, Some("Bob"));
+
+    let open_hub_component = instantiate_open_hub(&mut test_runner, &seller, package);
+
+    let (seller_key_resource, seller_key_local, seller_trader_component) =
+        create_open_trader(&mut test_runner, &seller, open_hub_component);
+
+    let (marketplace_component, marketplace_key) =
+        create_marketplace(&mut test_runner, &seller, package, dec!(0.02));
+
+    let nft_address = create_nft_resource(&mut test_runner, &seller, 0, 4, None);
+
+    for (number, price) in [(0, dec!(100)), (1, dec!(200)), (3, dec!(100))] {
+        list(
+            &mut test_runner,
+            &seller,
+            seller_trader_component,
+            seller_key_resource,
+            seller_key_local.clone(),
+            nft_address,
+            NonFungibleLocalId::integer(number),
+            price,
+            None,
+            vec![marketplace_key],
+        );
+    }
+
+    // The seller won't pay this marketplace's fee on the last listing.
+    set_listing_max_marketplace_fee(
+        &mut test_runner,
+        &seller,
+        seller_trader_component,
+        seller_key_resource,
+        seller_key_local,
+        create_global_id(nft_address, 3),
+        Some(dec!(0.01)),
+    );
+
+    let buyer_balance_before = test_runner.get_component_balance(buyer.account, XRD);
+
+    sweep_listings(
+        &mut test_runner,
+        &buyer,
+        marketplace_component,
+        vec![
+            // bought
+            (
+                seller_trader_component,
+                create_global_id(nft_address, 0),
+                dec!(150),
+            ),
+            // above the max price
+            (
+                seller_trader_component,
+                create_global_id(nft_address, 1),
+                dec!(150),
+            ),
+            // never listed
+            (
+                seller_trader_component,
+                create_global_id(nft_address, 2),
+                dec!(150),
+            ),
+            // marketplace fee above the listing's maximum
+            (
+                seller_trader_component,
+                create_global_id(nft_address, 3),
+                dec!(150),
+            ),
+        ],
+        dec!(600),
+    )
+    .expect_commit_success();
+
+    assert_eq!(
+        test_runner.get_component_balance(buyer.account, XRD),
+        buyer_balance_before - dec!(100)
+    );
+
+    let buyer_nfts = get_component_nflids(&mut test_runner, buyer.account, nft_address);
+
+    assert_eq!(buyer_nfts.len(), 1);
+    assert!(buyer_nfts.contains(&NonFungibleLocalId::integer(0)));
+
+    assert_eq!(
+        fee_balance(&mut test_runner, &buyer, marketplace_component, XRD),
+        dec!(2)
+    );
+}
@@ -66,7 +66,13 @@ pub fn list_royalty_nft(
             builder.call_method(
                 trader_component,
                 "royal_list",
-                manifest_args!(lookup.bucket("listing"), price, sell_currency, auth_buyers,),
+                manifest_args!(
+                    lookup.bucket("listing"),
+                    price,
+                    sell_currency,
+                    auth_buyers,
+                    None::<Decimal>,
+                ),
             )
         })
         .call_method(
@@ -265,6 +271,41 @@ pub fn list(
     currency: Option<ResourceAddress>,
     auth_buyers: Vec<ResourceAddress>,
 ) {
+    let receipt = try_list(
+        test_runner,
+        user,
+        trader_component,
+        trader_key_resource,
+        trader_key_local,
+        nft_address,
+        nft_local_id,
+        price,
+        currency,
+        auth_buyers,
+        None,
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Lists an NFT with the highest marketplace fee the seller accepts. The receipt is returned so tests can check
+/// rejected listings.
+pub fn try_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+    price: Decimal,
+    currency: Option<ResourceAddress>,
+    auth_buyers: Vec<ResourceAddress>,
+    max_marketplace_fee: Option<Decimal>,
+) -> TransactionReceipt {
     let sell_currency: ResourceAddress;
 
     if currency.is_some() {
@@ -290,14 +331,40 @@ pub fn list(
             builder.call_method(
                 trader_component,
                 "list",
-                manifest_args!(lookup.bucket("listing"), sell_currency, price, auth_buyers,),
+                manifest_args!(
+                    lookup.bucket("listing"),
+                    sell_currency,
+                    price,
+                    auth_buyers,
+                    max_marketplace_fee,
+                ),
             )
         })
         .build();
 
-    let receipt = test_runner.execute_manifest(
+    test_runner.execute_manifest(
         manifest,
         vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn purchase_listing(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    marketplace_component: ComponentAddress,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+    payment: Decimal,
+    currency: Option<ResourceAddress>,
+) {
+    let receipt = try_purchase_listing(
+        test_runner,
+        user,
+        marketplace_component,
+        trader_component,
+        nfgid,
+        payment,
+        currency,
     );
 
     if !receipt.is_commit_success() {
@@ -306,7 +373,8 @@ pub fn list(
     }
 }
 
-pub fn purchase_listing(
+/// Purchases a listing through a marketplace and returns the receipt so tests can check purchases that should be rejected.
+pub fn try_purchase_listing(
     test_runner: &mut DefaultLedgerSimulator,
     user: &User,
     marketplace_component: ComponentAddress,
@@ -314,7 +382,7 @@ pub fn purchase_listing(
     nfgid: NonFungibleGlobalId,
     payment: Decimal,
     currency: Option<ResourceAddress>,
-) {
+) -> TransactionReceipt {
     let buy_currency: ResourceAddress;
 
     if currency.is_some() {
@@ -335,12 +403,113 @@ pub fn purchase_listing(
             builder.call_method(
                 marketplace_component,
                 "purchase_listing",
-                manifest_args!(
-                    nfgid,
-                    lookup.bucket("payment"),
-                    trader_component,
-                    user.account,
-                ),
+                manifest_args!(nfgid, lookup.bucket("payment"), trader_component),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+#[derive(ManifestSbor)]
+pub enum OfferTarget {
+    Nft(NonFungibleGlobalId),
+    Collection(ResourceAddress),
+}
+
+pub fn make_offer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    escrow: Decimal,
+    targets: Vec<(OfferTarget, Decimal)>,
+) -> u64 {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(user.account, "withdraw", manifest_args!(XRD, escrow))
+        .take_all_from_worktop(XRD, "escrow")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "make_offer",
+                manifest_args!(lookup.bucket("escrow"), targets),
+            )
+        })
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    receipt.expect_commit(true).output(4)
+}
+
+pub fn accept_offer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    offer_trader_component: ComponentAddress,
+    offer_id: u64,
+    nft_address: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+) {
+    let receipt = try_accept_offer(
+        test_runner,
+        user,
+        offer_trader_component,
+        offer_id,
+        nft_address,
+        nft_local_id,
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Accepts an offer and returns the receipt so tests can check offers that should be rejected.
+pub fn try_accept_offer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    offer_trader_component: ComponentAddress,
+    offer_id: u64,
+    nft_address: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(nft_address, indexset![nft_local_id.clone()]),
+        )
+        .take_all_from_worktop(nft_address, "nft")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                offer_trader_component,
+                "accept_offer",
+                manifest_args!(offer_id, lookup.bucket("nft")),
             )
         })
         .call_method(
@@ -350,6 +519,180 @@ pub fn purchase_listing(
         )
         .build();
 
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn cancel_offer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    offer_id: u64,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(trader_component, "cancel_offer", manifest_args!(offer_id))
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn set_offer_expiry(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    offer_id: u64,
+    expiry: Option<Instant>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "set_offer_expiry",
+            manifest_args!(offer_id, expiry),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn listing_count(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+) -> u64 {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(trader_component, "listing_count", manifest_args!())
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+pub fn is_listed(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+) -> bool {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(trader_component, "is_listed", manifest_args!(nfgid))
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    receipt.expect_commit(true).output(1)
+}
+
+/// Returns the NFTs of a page of the trader account's listings, in index order.
+pub fn get_listings(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    start: u64,
+    limit: u64,
+) -> Vec<NonFungibleGlobalId> {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            trader_component,
+            "get_listings",
+            manifest_args!(start, limit),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    let listings: ScryptoValue = receipt.expect_commit(true).output(1);
+
+    let ScryptoValue::Array { elements, .. } = listings else {
+        panic!("Listings are not an array");
+    };
+
+    // Only the NFT of each listing is read - it's the fourth field of a Listing.
+    elements
+        .into_iter()
+        .map(|listing| match listing {
+            ScryptoValue::Tuple { fields } => {
+                scrypto_decode(&scrypto_encode(&fields[3]).unwrap()).unwrap()
+            }
+            _ => panic!("Listing is not a tuple"),
+        })
+        .collect()
+}
+
+pub fn cancel_listing(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(trader_component, "cancel_listing", manifest_args!(nfgid))
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
     let receipt = test_runner.execute_manifest(
         manifest,
         vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
@@ -360,3 +703,1025 @@ pub fn purchase_listing(
         panic!("TRANSACTION FAIL");
     }
 }
+
+#[derive(ManifestSbor)]
+pub struct AuctionTiming {
+    pub start_time: Instant,
+    pub end_time: Instant,
+    pub extension_seconds: i64,
+}
+
+pub fn auction_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+    reserve_price: Decimal,
+    minimum_increment: Decimal,
+    start_time: Instant,
+    end_time: Instant,
+    permissions: Vec<ResourceAddress>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(nft_address, indexset![nft_local_id.clone()]),
+        )
+        .take_all_from_worktop(nft_address, "nft")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "auction_list",
+                manifest_args!(
+                    lookup.bucket("nft"),
+                    XRD,
+                    reserve_price,
+                    minimum_increment,
+                    AuctionTiming {
+                        start_time,
+                        end_time,
+                        extension_seconds: 0,
+                    },
+                    permissions,
+                ),
+            )
+        })
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Places a bid in XRD on behalf of `bidder_account`, presenting a permission badge held by the user.
+/// The receipt is returned so tests can check bids that should be rejected.
+pub fn place_bid(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+    bid: Decimal,
+    permission: ResourceAddress,
+    bidder_account: ComponentAddress,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, bid))
+        .take_all_from_worktop(XRD, "bid")
+        .create_proof_from_account_of_amount(user.account, permission, dec!(1))
+        .pop_from_auth_zone("permission")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "place_bid",
+                manifest_args!(
+                    nfgid,
+                    lookup.bucket("bid"),
+                    lookup.proof("permission"),
+                    bidder_account,
+                ),
+            )
+        })
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Settles an auction - anyone can do this once it has ended.
+pub fn settle_auction(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(trader_component, "settle_auction", manifest_args!(nfgid))
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn cancel_auction(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(trader_component, "cancel_auction", manifest_args!(nfgid))
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+#[derive(ManifestSbor)]
+pub enum PriceDecay {
+    Linear,
+    Stepwise(i64),
+}
+
+#[derive(ManifestSbor)]
+pub struct DutchAuction {
+    pub start_price: Decimal,
+    pub floor_price: Decimal,
+    pub start_time: Instant,
+    pub end_time: Instant,
+    pub decay: PriceDecay,
+}
+
+pub fn dutch_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_id: NonFungibleLocalId,
+    dutch_auction: DutchAuction,
+    auth_buyers: Vec<ResourceAddress>,
+    max_marketplace_fee: Option<Decimal>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(nft_address, indexset![nft_local_id.clone()]),
+        )
+        .take_all_from_worktop(nft_address, "nft")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "dutch_list",
+                manifest_args!(
+                    lookup.bucket("nft"),
+                    XRD,
+                    dutch_auction,
+                    auth_buyers,
+                    max_marketplace_fee
+                ),
+            )
+        })
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn bundle_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_ids: Vec<NonFungibleLocalId>,
+    price: Decimal,
+    auth_buyers: Vec<ResourceAddress>,
+) -> u64 {
+    let receipt = try_bundle_list(
+        test_runner,
+        user,
+        trader_component,
+        trader_key_resource,
+        trader_key_local,
+        nft_address,
+        nft_local_ids,
+        price,
+        auth_buyers,
+        None,
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    receipt.expect_commit(true).output(4)
+}
+
+/// Lists a bundle and returns the receipt so tests can check bundles that should be rejected.
+pub fn try_bundle_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_ids: Vec<NonFungibleLocalId>,
+    price: Decimal,
+    auth_buyers: Vec<ResourceAddress>,
+    max_marketplace_fee: Option<Decimal>,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(
+                nft_address,
+                nft_local_ids.into_iter().collect::<IndexSet<_>>()
+            ),
+        )
+        .take_all_from_worktop(nft_address, "nfts")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "bundle_list",
+                manifest_args!(
+                    vec![lookup.bucket("nfts")],
+                    XRD,
+                    price,
+                    None::<HashMap<NonFungibleGlobalId, Decimal>>,
+                    auth_buyers,
+                    max_marketplace_fee,
+                ),
+            )
+        })
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+/// Lists a bundle of NFTs from any number of collections with a declared portion of the price for each NFT.
+/// The receipt is returned so tests can check price splits that should be rejected.
+pub fn try_split_bundle_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    price_split: HashMap<NonFungibleGlobalId, Decimal>,
+    auth_buyers: Vec<ResourceAddress>,
+) -> TransactionReceipt {
+    let price = price_split
+        .values()
+        .fold(Decimal::zero(), |total, portion| total + *portion);
+
+    let mut collections: IndexMap<ResourceAddress, IndexSet<NonFungibleLocalId>> = IndexMap::new();
+
+    for nfgid in price_split.keys() {
+        collections
+            .entry(nfgid.resource_address())
+            .or_default()
+            .insert(nfgid.local_id().clone());
+    }
+
+    let mut builder = ManifestBuilder::new().lock_fee_from_faucet().call_method(
+        user.account,
+        "create_proof_of_non_fungibles",
+        manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+    );
+
+    for (nft_address, nft_local_ids) in collections.iter() {
+        builder = builder.call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(*nft_address, nft_local_ids.clone()),
+        );
+    }
+
+    let manifest = builder
+        .call_method(
+            trader_component,
+            "bundle_list",
+            manifest_args!(
+                ManifestExpression::EntireWorktop,
+                XRD,
+                price,
+                Some(price_split),
+                auth_buyers,
+                None::<Decimal>,
+            ),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn add_bundle_private_buyer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    bundle_id: u64,
+    buyer: NonFungibleGlobalId,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "add_bundle_private_buyer",
+            manifest_args!(bundle_id, buyer),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Purchases a bundle directly from the trader account with a proof of a specific badge - e.g. a private deal
+/// targeted at the buyer's OpenTrade trader key. The receipt is returned so tests can check rejected buyers.
+pub fn purchase_bundle_with_badge(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    bundle_id: u64,
+    payment: Decimal,
+    badge_resource: ResourceAddress,
+    badge_local: NonFungibleLocalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .create_proof_from_account_of_non_fungibles(
+            user.account,
+            badge_resource,
+            indexset![badge_local],
+        )
+        .pop_from_auth_zone("badge")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "purchase_bundle",
+                manifest_args!(
+                    bundle_id,
+                    lookup.bucket("payment"),
+                    lookup.proof("badge"),
+                    user.account,
+                ),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn cancel_bundle(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    bundle_id: u64,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(trader_component, "cancel_bundle", manifest_args!(bundle_id))
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn change_price(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+    new_price: Decimal,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "change_price",
+            manifest_args!(nfgid, new_price),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Sets a listing's price in another currency and returns the receipt so tests can check prices that should be rejected.
+pub fn set_alternative_price(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+    currency: ResourceAddress,
+    price: Decimal,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "set_alternative_price",
+            manifest_args!(nfgid, currency, price),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn set_listing_expiry(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+    expiry: Option<Instant>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "set_listing_expiry",
+            manifest_args!(nfgid, expiry),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Returns expired listings to their seller - anyone can call this. The receipt is returned so tests can
+/// check that listings which haven't expired are rejected.
+pub fn purge_expired(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    nfgids: Vec<NonFungibleGlobalId>,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(trader_component, "purge_expired", manifest_args!(nfgids))
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn add_private_buyer(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+    buyer: NonFungibleGlobalId,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "add_private_buyer",
+            manifest_args!(nfgid, buyer),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Purchases a listing directly from the trader account with a proof of a specific badge - e.g. a private deal
+/// targeted at the buyer's OpenTrade trader key. The receipt is returned so tests can check rejected buyers.
+pub fn purchase_listing_with_badge(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    nfgid: NonFungibleGlobalId,
+    payment: Decimal,
+    badge_resource: ResourceAddress,
+    badge_local: NonFungibleLocalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, payment))
+        .take_all_from_worktop(XRD, "payment")
+        .create_proof_from_account_of_non_fungibles(
+            user.account,
+            badge_resource,
+            indexset![badge_local],
+        )
+        .pop_from_auth_zone("badge")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "purchase_listing",
+                manifest_args!(nfgid, lookup.bucket("payment"), lookup.proof("badge")),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+#[derive(ManifestSbor, Clone)]
+pub enum SwapAsset {
+    Fungible(ResourceAddress, Decimal),
+    Nft(NonFungibleGlobalId),
+}
+
+/// Proposes a swap of one NFT from the user's account for the requested assets, optionally escrowing an XRD deposit
+/// for the swap royalty of the offered NFT. Returns the swap id.
+pub fn propose_swap(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    offered: NonFungibleGlobalId,
+    requested: Vec<SwapAsset>,
+    royalty_deposit: Option<Decimal>,
+    counterparty: Option<NonFungibleGlobalId>,
+) -> u64 {
+    let mut builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(
+                offered.resource_address(),
+                indexset![offered.local_id().clone()]
+            ),
+        )
+        .take_all_from_worktop(offered.resource_address(), "offered");
+
+    // lock fee, proof, withdraw and take come before the call to propose_swap
+    let mut output_index = 4;
+
+    if let Some(royalty_deposit) = royalty_deposit {
+        builder = builder
+            .call_method(
+                user.account,
+                "withdraw",
+                manifest_args!(XRD, royalty_deposit),
+            )
+            .take_all_from_worktop(XRD, "royalty_deposit");
+
+        output_index += 2;
+    }
+
+    let manifest = builder
+        .with_name_lookup(|builder, lookup| {
+            let royalty_payment = royalty_deposit.map(|_| lookup.bucket("royalty_deposit"));
+
+            builder.call_method(
+                trader_component,
+                "propose_swap",
+                manifest_args!(
+                    vec![lookup.bucket("offered")],
+                    requested,
+                    royalty_payment,
+                    counterparty
+                ),
+            )
+        })
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+
+    receipt.expect_commit(true).output(output_index)
+}
+
+/// Accepts a swap that requests XRD, showing the given badge as the counterparty. The receipt is returned so tests
+/// can check rejected counterparties.
+pub fn accept_swap(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    swap_id: u64,
+    provided: Decimal,
+    badge_resource: ResourceAddress,
+    badge_local: NonFungibleLocalId,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(user.account, "withdraw", manifest_args!(XRD, provided))
+        .take_all_from_worktop(XRD, "provided")
+        .create_proof_from_account_of_non_fungibles(
+            user.account,
+            badge_resource,
+            indexset![badge_local],
+        )
+        .pop_from_auth_zone("badge")
+        .with_name_lookup(|builder, lookup| {
+            let royalty_payment: Option<ManifestBucket> = None;
+
+            builder.call_method(
+                trader_component,
+                "accept_swap",
+                manifest_args!(
+                    swap_id,
+                    vec![lookup.bucket("provided")],
+                    royalty_payment,
+                    lookup.proof("badge"),
+                    user.account
+                ),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn cancel_swap(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    swap_id: u64,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(trader_component, "cancel_swap", manifest_args!(swap_id))
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+#[derive(ManifestSbor, Clone)]
+pub struct ListingTerms {
+    pub currency: ResourceAddress,
+    pub price: Decimal,
+    pub permissions: Vec<ResourceAddress>,
+    pub alternative_prices: HashMap<ResourceAddress, Decimal>,
+    pub max_marketplace_fee: Option<Decimal>,
+}
+
+/// Lists NFTs of one collection in a single batch. The receipt is returned so tests can check rejected batches.
+pub fn batch_list(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_address: ResourceAddress,
+    nft_local_ids: IndexSet<NonFungibleLocalId>,
+    terms: HashMap<NonFungibleGlobalId, ListingTerms>,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            user.account,
+            "withdraw_non_fungibles",
+            manifest_args!(nft_address, nft_local_ids),
+        )
+        .take_all_from_worktop(nft_address, "nfts")
+        .with_name_lookup(|builder, lookup| {
+            builder.call_method(
+                trader_component,
+                "batch_list",
+                manifest_args!(vec![lookup.bucket("nfts")], terms),
+            )
+        })
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}
+
+pub fn batch_change_price(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    changes: Vec<(NonFungibleGlobalId, ResourceAddress, Decimal)>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "batch_change_price",
+            manifest_args!(changes),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn batch_cancel_listings(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nft_ids: Vec<NonFungibleGlobalId>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "batch_cancel_listings",
+            manifest_args!(nft_ids),
+        )
+        .call_method(
+            user.account,
+            "deposit_batch",
+            manifest_args!(ManifestExpression::EntireWorktop),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+pub fn set_listing_max_marketplace_fee(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgid: NonFungibleGlobalId,
+    max_marketplace_fee: Option<Decimal>,
+) {
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "set_listing_max_marketplace_fee",
+            manifest_args!(nfgid, max_marketplace_fee),
+        )
+        .build();
+
+    let receipt = test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    );
+
+    if !receipt.is_commit_success() {
+        println!("{:?}", receipt);
+        panic!("TRANSACTION FAIL");
+    }
+}
+
+/// Creates proofs of listed NFTs from the trader account's escrow and checks each proof reached the auth zone.
+/// The receipt is returned so tests can check rejected proof requests.
+pub fn create_listed_nft_proofs(
+    test_runner: &mut DefaultLedgerSimulator,
+    user: &User,
+    trader_component: ComponentAddress,
+    trader_key_resource: ResourceAddress,
+    trader_key_local: NonFungibleLocalId,
+    nfgids: Vec<NonFungibleGlobalId>,
+) -> TransactionReceipt {
+    let mut builder = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .call_method(
+            user.account,
+            "create_proof_of_non_fungibles",
+            manifest_args!(trader_key_resource, indexset![trader_key_local.clone()]),
+        )
+        .call_method(
+            trader_component,
+            "create_listed_nft_proofs",
+            manifest_args!(nfgids.clone()),
+        );
+
+    for (index, nfgid) in nfgids.into_iter().enumerate() {
+        let (nft_address, nft_local) = nfgid.into_parts();
+
+        builder = builder.create_proof_from_auth_zone_of_non_fungibles(
+            nft_address,
+            [nft_local],
+            format!("nft_proof_{}", index),
+        );
+    }
+
+    let manifest = builder.build();
+
+    test_runner.execute_manifest(
+        manifest,
+        vec![NonFungibleGlobalId::from_public_key(&user.pubkey)],
+    )
+}